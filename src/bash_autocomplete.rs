@@ -0,0 +1,138 @@
+use crate::runner::Runner;
+use crate::zsh_autocomplete::{current_word, get_completion_items, CompletionItems};
+
+/// Characters from the default COMP_WORDBREAKS that can appear in task names
+/// and arguments. Bash replaces only the part after them so the printed
+/// candidates must not include it.
+const WORD_BREAKS: [char; 2] = [':', '='];
+
+fn get_bash_completion_code(items: &CompletionItems, word: &str) -> String {
+    let candidates: Vec<&str> = match items {
+        CompletionItems::Files => return String::new(),
+        CompletionItems::Tasks(tasks) => tasks.iter().map(|(_, task)| task.name.as_str()).collect(),
+        CompletionItems::Args(args) => args.iter().map(|(value, _)| value.as_str()).collect(),
+    };
+
+    let replaced = word.rfind(WORD_BREAKS).map(|index| index + 1).unwrap_or(0);
+
+    let mut out = String::new();
+
+    for candidate in candidates {
+        if !candidate.starts_with(word) {
+            continue;
+        }
+
+        out.push_str(&candidate[replaced..]);
+        out.push('\n');
+    }
+
+    return out;
+}
+
+pub fn get_bash_autocompletion(
+    runners: &Vec<Box<dyn Runner>>,
+    comp_line: &str,
+    comp_point: Option<usize>,
+) -> String {
+    let mut point = comp_point.unwrap_or(comp_line.len()).min(comp_line.len());
+    while !comp_line.is_char_boundary(point) {
+        point -= 1;
+    }
+
+    let (lbuffer, rbuffer) = comp_line.split_at(point);
+    let items = get_completion_items(runners, lbuffer, rbuffer);
+    return get_bash_completion_code(&items, &current_word(lbuffer));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_runner::{deploy_task, TestRunner};
+
+    fn runners(tasks: &[&str]) -> Vec<Box<dyn Runner>> {
+        let tasks = tasks.iter().map(|t| t.to_string()).collect();
+        return vec![Box::new(TestRunner::new("runner1".to_string(), tasks))];
+    }
+
+    fn deploy_runners(tasks: &[&str]) -> Vec<Box<dyn Runner>> {
+        let tasks = tasks.iter().map(|t| t.to_string()).collect();
+        let mut runner = TestRunner::new("runner1".to_string(), tasks);
+        runner.tasks.push(deploy_task());
+        return vec![Box::new(runner)];
+    }
+
+    #[test]
+    fn test_filters_tasks_by_current_word() {
+        let runners = runners(&["build", "bundle", "test"]);
+
+        assert_eq!(
+            get_bash_autocompletion(&runners, "rt b", None),
+            "build\nbundle\n"
+        );
+        assert_eq!(
+            get_bash_autocompletion(&runners, "rt ", None),
+            "build\nbundle\ntest\n"
+        );
+    }
+
+    #[test]
+    fn test_uses_comp_point() {
+        let runners = runners(&["build", "test"]);
+
+        // Cursor after "rt t" in "rt t --watch"
+        assert_eq!(
+            get_bash_autocompletion(&runners, "rt t --watch", Some(4)),
+            "test\n"
+        );
+    }
+
+    #[test]
+    fn test_trims_colon_prefix() {
+        let runners = runners(&["db:migrate", "db:seed", "dev"]);
+
+        assert_eq!(
+            get_bash_autocompletion(&runners, "rt db:m", None),
+            "migrate\n"
+        );
+        assert_eq!(
+            get_bash_autocompletion(&runners, "rt d", None),
+            "db:migrate\ndb:seed\ndev\n"
+        );
+    }
+
+    #[test]
+    fn test_files_and_args() {
+        let runners = deploy_runners(&[]);
+
+        assert_eq!(
+            get_bash_autocompletion(&runners, "rt deploy ", None),
+            "prod\n"
+        );
+        assert_eq!(
+            get_bash_autocompletion(&runners, "rt deploy prod ", None),
+            ""
+        );
+    }
+
+    #[test]
+    fn test_after_rt_flags() {
+        let runners = deploy_runners(&["build"]);
+
+        for flag in [
+            "-p",
+            "--parallel",
+            "--dry-run",
+            "--which",
+            "--exec",
+            "--watch",
+        ] {
+            let line = format!("rt {} b", flag);
+            assert_eq!(get_bash_autocompletion(&runners, &line, None), "build\n");
+        }
+
+        assert_eq!(
+            get_bash_autocompletion(&runners, "rt --exec deploy ", None),
+            "prod\n"
+        );
+    }
+}
@@ -0,0 +1,332 @@
+use crate::runner::{Runner, Task};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Entries not written for this long are removed when storing
+const MAX_AGE: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+/// Only the most recently written entries are kept
+const MAX_ENTRIES: usize = 200;
+
+/// Modification time, size and mode of a file or directory. All are None when
+/// the path does not exist so creating a marker file invalidates the cache
+/// too. The mode notices `chmod +x` which does not change the time.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct FileStamp {
+    path: PathBuf,
+    modified: Option<(u64, u32)>,
+    size: Option<u64>,
+    mode: Option<u32>,
+}
+
+impl FileStamp {
+    fn read(project_dir: &Path, path: &Path) -> Self {
+        let metadata = fs::metadata(project_dir.join(path)).ok();
+
+        let modified = metadata
+            .as_ref()
+            .and_then(|m| m.modified().ok())
+            .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+            .map(|duration| (duration.as_secs(), duration.subsec_nanos()));
+
+        return FileStamp {
+            path: path.to_path_buf(),
+            modified,
+            size: metadata.as_ref().map(|m| m.len()),
+            mode: metadata.map(|m| m.permissions().mode()),
+        };
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CacheEntry {
+    version: String,
+    project_dir: PathBuf,
+    runner: String,
+    files: Vec<FileStamp>,
+    tasks: Vec<Task>,
+}
+
+/// Directory for the completion cache. `RT_NO_CACHE` disables caching.
+pub fn cache_dir() -> Option<PathBuf> {
+    if env::var_os("RT_NO_CACHE").is_some() {
+        return None;
+    }
+
+    let xdg_cache = env::var_os("XDG_CACHE_HOME")
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute());
+
+    if let Some(dir) = xdg_cache {
+        return Some(dir.join("rt"));
+    }
+
+    return env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache").join("rt"));
+}
+
+/// FNV-1a is used instead of the std hasher which is not stable between
+/// Rust versions
+fn entry_path(cache_dir: &Path, project_dir: &Path, runner: &str) -> PathBuf {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    let key = format!("{}\0{}", project_dir.display(), runner);
+
+    for byte in key.bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+
+    return cache_dir.join(format!("{:016x}.json", hash));
+}
+
+/// Paths whose changes invalidate the cached tasks. Directories of the task
+/// sources are included so added and removed files are noticed.
+fn watched_files(runner: &dyn Runner) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = Vec::new();
+
+    let listed = runner
+        .marker_files()
+        .into_iter()
+        .chain(runner.cache_files())
+        .map(PathBuf::from);
+
+    let sources = runner
+        .tasks()
+        .iter()
+        .filter_map(|task| task.source.clone())
+        .flat_map(|source| {
+            let dir = source.parent().map(|dir| dir.to_path_buf());
+            return std::iter::once(source).chain(dir);
+        });
+
+    for file in listed.chain(sources) {
+        if !file.as_os_str().is_empty() && !files.contains(&file) {
+            files.push(file);
+        }
+    }
+
+    return files;
+}
+
+/// Restore the runner tasks from the cache. Returns false when there is no
+/// entry or any of the files it was created from have changed.
+pub fn restore(cache_dir: &Path, project_dir: &Path, runner: &mut dyn Runner) -> bool {
+    let path = entry_path(cache_dir, project_dir, runner.name());
+
+    let Ok(content) = fs::read_to_string(path) else {
+        return false;
+    };
+
+    let Ok(entry) = serde_json::from_str::<CacheEntry>(&content) else {
+        return false;
+    };
+
+    let is_fresh = entry.version == crate::VERSION
+        && entry.project_dir == project_dir
+        && entry.runner == runner.name()
+        && entry
+            .files
+            .iter()
+            .all(|stamp| *stamp == FileStamp::read(project_dir, &stamp.path));
+
+    if !is_fresh {
+        return false;
+    }
+
+    runner.restore_tasks(entry.tasks);
+    return true;
+}
+
+/// Save the tasks of a loaded runner
+pub fn store(cache_dir: &Path, project_dir: &Path, runner: &dyn Runner) -> Result<()> {
+    let entry = CacheEntry {
+        version: crate::VERSION.to_string(),
+        project_dir: project_dir.to_path_buf(),
+        runner: runner.name().to_string(),
+        files: watched_files(runner)
+            .iter()
+            .map(|path| FileStamp::read(project_dir, path))
+            .collect(),
+        tasks: runner.tasks().clone(),
+    };
+
+    fs::create_dir_all(cache_dir)
+        .with_context(|| format!("Failed to create {}", cache_dir.display()))?;
+
+    // Write to a temporary file first so concurrent completions never read
+    // a partial entry
+    let path = entry_path(cache_dir, project_dir, runner.name());
+    let tmp_path = path.with_extension(format!("json.{}.tmp", std::process::id()));
+
+    fs::write(&tmp_path, serde_json::to_string(&entry)?)
+        .with_context(|| format!("Failed to write {}", tmp_path.display()))?;
+    fs::rename(&tmp_path, &path).with_context(|| format!("Failed to write {}", path.display()))?;
+
+    prune(cache_dir, MAX_AGE, MAX_ENTRIES);
+
+    return Ok(());
+}
+
+/// Remove entries older than `max_age` and all but the `max_entries` newest.
+/// Leftover temporary files of interrupted writes are removed too.
+fn prune(cache_dir: &Path, max_age: Duration, max_entries: usize) {
+    let Ok(entries) = fs::read_dir(cache_dir) else {
+        return;
+    };
+
+    let mut files: Vec<(SystemTime, PathBuf)> = entries
+        .flatten()
+        .filter(|entry| {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            return name.ends_with(".json") || name.ends_with(".tmp");
+        })
+        .filter_map(|entry| {
+            let modified = entry.metadata().and_then(|m| m.modified()).ok()?;
+            return Some((modified, entry.path()));
+        })
+        .collect();
+
+    // Newest first
+    files.sort_by(|a, b| b.0.cmp(&a.0));
+
+    let now = SystemTime::now();
+    for (index, (modified, path)) in files.iter().enumerate() {
+        let age = now.duration_since(*modified).unwrap_or_default();
+        let is_tmp = path.extension().is_some_and(|ext| ext == "tmp");
+
+        if index >= max_entries || age > max_age || (is_tmp && age > Duration::from_secs(60)) {
+            fs::remove_file(path).ok();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_runner::TestRunner;
+
+    fn test_dirs(name: &str) -> (PathBuf, PathBuf) {
+        let dir = env::temp_dir().join(format!("rt-cache-test-{}-{}", name, std::process::id()));
+        fs::remove_dir_all(&dir).ok();
+
+        let project_dir = dir.join("project");
+        fs::create_dir_all(project_dir.join("tasks")).unwrap();
+        return (dir.join("cache"), project_dir);
+    }
+
+    fn loaded_runner() -> TestRunner {
+        let mut runner = TestRunner::new("runner1".to_string(), vec!["build".to_string()]);
+        runner.tasks[0].source = Some(PathBuf::from("tasks/build.sh"));
+        runner.tasks[0].description = Some("Build it".to_string());
+        return runner;
+    }
+
+    #[test]
+    fn test_restores_until_sources_change() {
+        let (cache_dir, project_dir) = test_dirs("sources");
+        fs::write(project_dir.join("tasks/build.sh"), "make").unwrap();
+
+        let runner = loaded_runner();
+        store(&cache_dir, &project_dir, &runner).unwrap();
+
+        let mut restored = TestRunner::new("runner1".to_string(), Vec::new());
+        assert!(restore(&cache_dir, &project_dir, &mut restored));
+        assert_eq!(restored.tasks, runner.tasks);
+
+        fs::write(project_dir.join("tasks/build.sh"), "make all").unwrap();
+        let mut restored = TestRunner::new("runner1".to_string(), Vec::new());
+        assert!(!restore(&cache_dir, &project_dir, &mut restored));
+        assert!(restored.tasks.is_empty());
+
+        fs::remove_dir_all(cache_dir.parent().unwrap()).ok();
+    }
+
+    #[test]
+    fn test_invalidated_by_new_files() {
+        let (cache_dir, project_dir) = test_dirs("new-files");
+        fs::write(project_dir.join("tasks/build.sh"), "make").unwrap();
+
+        store(&cache_dir, &project_dir, &loaded_runner()).unwrap();
+
+        // The marker file of the test runner is its name
+        fs::write(project_dir.join("runner1"), "").unwrap();
+        let mut restored = TestRunner::new("runner1".to_string(), Vec::new());
+        assert!(!restore(&cache_dir, &project_dir, &mut restored));
+
+        fs::remove_dir_all(cache_dir.parent().unwrap()).ok();
+    }
+
+    #[test]
+    fn test_invalidated_by_mode_changes() {
+        let (cache_dir, project_dir) = test_dirs("mode");
+        let script = project_dir.join("tasks/build.sh");
+        fs::write(&script, "make").unwrap();
+
+        store(&cache_dir, &project_dir, &loaded_runner()).unwrap();
+
+        fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+        let mut restored = TestRunner::new("runner1".to_string(), Vec::new());
+        assert!(!restore(&cache_dir, &project_dir, &mut restored));
+
+        fs::remove_dir_all(cache_dir.parent().unwrap()).ok();
+    }
+
+    #[test]
+    fn test_prune() {
+        let (cache_dir, _) = test_dirs("prune");
+        fs::create_dir_all(&cache_dir).unwrap();
+
+        let now = SystemTime::now();
+        let write = |name: &str, age: u64| {
+            let path = cache_dir.join(name);
+            let file = fs::File::create(&path).unwrap();
+            file.set_modified(now - Duration::from_secs(age)).unwrap();
+            return path;
+        };
+
+        let newest = write("a.json", 0);
+        let newer = write("b.json", 10);
+        let old = write("c.json", 20);
+        let expired = write("d.json", 1000);
+        let tmp = write("e.json.1.tmp", 100);
+        let other = write("notes.txt", 1000);
+
+        prune(&cache_dir, Duration::from_secs(500), 3);
+
+        assert!(newest.exists());
+        assert!(newer.exists());
+        assert!(old.exists());
+        assert!(!expired.exists());
+        assert!(!tmp.exists());
+        assert!(other.exists());
+
+        prune(&cache_dir, Duration::from_secs(500), 2);
+        assert!(!old.exists());
+        assert!(newer.exists());
+
+        fs::remove_dir_all(cache_dir.parent().unwrap()).ok();
+    }
+
+    #[test]
+    fn test_keyed_by_project_and_runner() {
+        let (cache_dir, project_dir) = test_dirs("keys");
+        store(&cache_dir, &project_dir, &loaded_runner()).unwrap();
+
+        let mut other_runner = TestRunner::new("runner2".to_string(), Vec::new());
+        assert!(!restore(&cache_dir, &project_dir, &mut other_runner));
+
+        let mut runner = TestRunner::new("runner1".to_string(), Vec::new());
+        assert!(!restore(
+            &cache_dir,
+            &project_dir.join("tasks"),
+            &mut runner
+        ));
+        assert!(restore(&cache_dir, &project_dir, &mut runner));
+
+        fs::remove_dir_all(cache_dir.parent().unwrap()).ok();
+    }
+}
@@ -0,0 +1,526 @@
+use super::runner::{find_table_key_line, Runner, Task};
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::{fs, io::ErrorKind};
+
+const CONFIG_FILES: [&str; 2] = [".cargo/config.toml", ".cargo/config"];
+
+#[derive(Debug, Default, Deserialize)]
+struct CargoManifest {
+    package: Option<CargoPackage>,
+    #[serde(default)]
+    bin: Vec<CargoBin>,
+    workspace: Option<CargoWorkspace>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CargoPackage {
+    name: String,
+    autobins: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CargoBin {
+    name: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CargoWorkspace {
+    #[serde(default)]
+    members: Vec<String>,
+    #[serde(default)]
+    exclude: Vec<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct CargoConfig {
+    #[serde(default)]
+    alias: BTreeMap<String, toml::Value>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum CargoTaskKind {
+    Alias(String),
+    Xtask(Option<String>),
+    Bin,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct CargoTask {
+    task: Task,
+    kind: CargoTaskKind,
+}
+
+fn read_optional(path: &Path) -> Result<Option<String>> {
+    return match fs::read_to_string(path) {
+        Ok(content) => Ok(Some(content)),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e).with_context(|| format!("Failed to read {}", path.display())),
+    };
+}
+
+fn parse_manifest(content: &str) -> Result<CargoManifest> {
+    return toml::from_str(content).context("Failed to parse Cargo.toml");
+}
+
+/// Returns alias names with their expansions
+fn parse_aliases(content: &str) -> Result<Vec<(String, String)>> {
+    let config: CargoConfig = toml::from_str(content).context("Failed to parse cargo config")?;
+    let mut aliases = Vec::new();
+
+    for (name, value) in config.alias {
+        let expansion = match value {
+            toml::Value::String(command) => command,
+            toml::Value::Array(parts) => parts
+                .iter()
+                .filter_map(|part| part.as_str())
+                .collect::<Vec<&str>>()
+                .join(" "),
+            _ => continue,
+        };
+
+        aliases.push((name, expansion));
+    }
+
+    return Ok(aliases);
+}
+
+/// Find subcommands with their line numbers from match arms like
+/// `"dist" => dist()` or `Some("dist") => ...` in the xtask sources
+fn find_xtask_commands(source: &str) -> Vec<(String, usize)> {
+    let mut commands: Vec<(String, usize)> = Vec::new();
+
+    for (index, line) in source.lines().enumerate() {
+        let line = line.trim();
+        let Some((pattern, _)) = line.split_once("=>") else {
+            continue;
+        };
+
+        for alternative in pattern.split('|') {
+            let alternative = alternative.trim();
+            let alternative = alternative
+                .strip_prefix("Some(")
+                .and_then(|a| a.strip_suffix(')'))
+                .unwrap_or(alternative);
+
+            let Some(command) = alternative
+                .strip_prefix('"')
+                .and_then(|a| a.strip_suffix('"'))
+            else {
+                continue;
+            };
+
+            let is_command_like = !command.is_empty()
+                && !command.starts_with('-')
+                && command
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+
+            if is_command_like && !commands.iter().any(|(c, _)| c == command) {
+                commands.push((command.to_string(), index + 1));
+            }
+        }
+    }
+
+    return commands;
+}
+
+/// Binaries of a single package following cargo's auto discovery rules
+fn package_bins(dir: &Path, manifest: &CargoManifest) -> Vec<String> {
+    let mut bins: Vec<String> = Vec::new();
+
+    for bin in &manifest.bin {
+        if let Some(name) = &bin.name {
+            bins.push(name.clone());
+        }
+    }
+
+    let Some(package) = &manifest.package else {
+        return bins;
+    };
+
+    if package.autobins == Some(false) {
+        return bins;
+    }
+
+    if dir.join("src/main.rs").exists() {
+        bins.push(package.name.clone());
+    }
+
+    if let Ok(entries) = fs::read_dir(dir.join("src/bin")) {
+        let mut discovered: Vec<String> = Vec::new();
+
+        for entry in entries.flatten() {
+            let path = entry.path();
+
+            let name = if path.is_dir() && path.join("main.rs").exists() {
+                path.file_name()
+            } else if path.extension().is_some_and(|ext| ext == "rs") {
+                path.file_stem()
+            } else {
+                None
+            };
+
+            if let Some(name) = name {
+                discovered.push(name.to_string_lossy().to_string());
+            }
+        }
+
+        discovered.sort();
+        bins.extend(discovered);
+    }
+
+    bins.dedup();
+    return bins;
+}
+
+/// Expand workspace members. Only trailing `/*` globs are supported.
+fn workspace_members(workspace: &CargoWorkspace) -> Vec<PathBuf> {
+    let mut members: Vec<PathBuf> = Vec::new();
+
+    for member in &workspace.members {
+        if let Some(parent) = member.strip_suffix("/*") {
+            let Ok(entries) = fs::read_dir(parent) else {
+                continue;
+            };
+
+            let mut dirs: Vec<PathBuf> = entries
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|path| path.join("Cargo.toml").exists())
+                .collect();
+            dirs.sort();
+            members.extend(dirs);
+        } else {
+            members.push(PathBuf::from(member));
+        }
+    }
+
+    return members
+        .into_iter()
+        .filter(|member| {
+            !workspace
+                .exclude
+                .iter()
+                .any(|exclude| member == Path::new(exclude))
+        })
+        .collect();
+}
+
+fn is_xtask_dir(path: &Path) -> bool {
+    return path.file_name().is_some_and(|name| name == "xtask");
+}
+
+pub struct CargoRunner {
+    tasks: Vec<Task>,
+    kinds: HashMap<String, CargoTaskKind>,
+    /// Files of the workspace members the bins are read from
+    member_files: Vec<String>,
+}
+
+impl CargoRunner {
+    pub fn new() -> Self {
+        return CargoRunner {
+            tasks: Vec::new(),
+            kinds: HashMap::new(),
+            member_files: Vec::new(),
+        };
+    }
+
+    fn read_xtask_commands(dir: &Path) -> Result<Vec<(String, usize)>> {
+        let Some(source) = read_optional(&dir.join("src/main.rs"))? else {
+            return Ok(Vec::new());
+        };
+
+        return Ok(find_xtask_commands(&source));
+    }
+
+    /// Returns the tasks and the files of the workspace members
+    fn read_cargo_project() -> Result<(Vec<CargoTask>, Vec<String>)> {
+        let mut tasks: Vec<CargoTask> = Vec::new();
+        let mut member_files: Vec<String> = Vec::new();
+
+        let Some(content) = read_optional(Path::new("Cargo.toml"))? else {
+            return Ok((tasks, member_files));
+        };
+        let manifest = parse_manifest(&content)?;
+
+        for config_file in CONFIG_FILES {
+            let Some(config) = read_optional(Path::new(config_file))? else {
+                continue;
+            };
+
+            let aliases = parse_aliases(&config)
+                .with_context(|| format!("Failed to parse {}", config_file))?;
+
+            for (name, expansion) in aliases {
+                let mut task = Task::new(name.as_str());
+                task.command = Some(format!("cargo {}", expansion));
+                task.source = Some(config_file.into());
+                task.line = find_table_key_line(&config, "alias", &name);
+
+                tasks.push(CargoTask {
+                    task,
+                    kind: CargoTaskKind::Alias(expansion),
+                });
+            }
+            break;
+        }
+
+        let mut packages: Vec<PathBuf> = vec![PathBuf::from(".")];
+        if let Some(workspace) = &manifest.workspace {
+            packages.extend(workspace_members(workspace));
+
+            // Added members change the directory of a glob
+            for member in &workspace.members {
+                if let Some(parent) = member.strip_suffix("/*") {
+                    member_files.push(parent.to_string());
+                }
+            }
+        }
+
+        let mut xtask_dir = packages.iter().find(|dir| is_xtask_dir(dir)).cloned();
+        if xtask_dir.is_none() && Path::new("xtask/Cargo.toml").exists() {
+            xtask_dir = Some(PathBuf::from("xtask"));
+        }
+
+        if let Some(dir) = &xtask_dir {
+            let source = dir.join("src/main.rs");
+
+            let mut task = Task::new("xtask");
+            task.command = Some("cargo xtask".to_string());
+            task.source = Some(source.clone());
+            tasks.push(CargoTask {
+                task,
+                kind: CargoTaskKind::Xtask(None),
+            });
+
+            for (command, line) in CargoRunner::read_xtask_commands(dir)? {
+                let mut task = Task::new(format!("xtask:{}", command));
+                task.command = Some(format!("cargo xtask {}", command));
+                task.source = Some(source.clone());
+                task.line = Some(line);
+
+                tasks.push(CargoTask {
+                    task,
+                    kind: CargoTaskKind::Xtask(Some(command)),
+                });
+            }
+        }
+
+        for dir in packages {
+            if is_xtask_dir(&dir) {
+                continue;
+            }
+
+            let bins = if dir == Path::new(".") {
+                package_bins(&dir, &manifest)
+            } else {
+                for file in ["Cargo.toml", "src/main.rs", "src/bin"] {
+                    member_files.push(dir.join(file).to_string_lossy().to_string());
+                }
+
+                let Some(content) = read_optional(&dir.join("Cargo.toml"))? else {
+                    continue;
+                };
+                let member = parse_manifest(&content)
+                    .with_context(|| format!("Failed to parse {}", dir.display()))?;
+                package_bins(&dir, &member)
+            };
+
+            for name in bins {
+                let mut task = Task::new(name.as_str());
+                task.command = Some(format!("cargo run --bin {}", name));
+                task.source = Some(dir.join("Cargo.toml"));
+
+                tasks.push(CargoTask {
+                    task,
+                    kind: CargoTaskKind::Bin,
+                });
+            }
+        }
+
+        let mut seen: Vec<String> = Vec::new();
+        tasks.retain(|cargo_task| {
+            if seen.contains(&cargo_task.task.name) {
+                return false;
+            }
+            seen.push(cargo_task.task.name.clone());
+            return true;
+        });
+
+        return Ok((tasks, member_files));
+    }
+}
+
+impl Runner for CargoRunner {
+    fn name(&self) -> &'static str {
+        return "Cargo.toml";
+    }
+
+    fn cache_files(&self) -> Vec<&str> {
+        let mut files = CONFIG_FILES.to_vec();
+        files.extend(["src/main.rs", "src/bin", "xtask/Cargo.toml"]);
+        files.extend(self.member_files.iter().map(String::as_str));
+        return files;
+    }
+
+    fn tasks(&self) -> &Vec<Task> {
+        return &self.tasks;
+    }
+
+    fn tasks_mut(&mut self) -> &mut Vec<Task> {
+        return &mut self.tasks;
+    }
+
+    fn load(&mut self) -> Result<()> {
+        let (cargo_tasks, member_files) =
+            CargoRunner::read_cargo_project().context("Failed to read Cargo.toml")?;
+        self.member_files = member_files;
+
+        self.tasks.clear();
+        self.kinds.clear();
+
+        for cargo_task in cargo_tasks {
+            self.kinds
+                .insert(cargo_task.task.name.clone(), cargo_task.kind);
+            self.tasks.push(cargo_task.task);
+        }
+
+        return Ok(());
+    }
+
+    fn command(&self, task: &str, args: &[String]) -> Result<Command> {
+        let mut cargo = Command::new("cargo");
+
+        let kind = self.kinds.get(task).cloned().unwrap_or(CargoTaskKind::Bin);
+
+        match kind {
+            CargoTaskKind::Alias(_) => cargo.arg(task).args(args),
+            CargoTaskKind::Xtask(command) => cargo.arg("xtask").args(command).args(args),
+            CargoTaskKind::Bin => cargo.arg("run").arg("--bin").arg(task).arg("--").args(args),
+        };
+
+        return Ok(cargo);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_aliases() {
+        let config = r#"
+[alias]
+b = "build --release"
+xtask = ["run", "--package", "xtask", "--"]
+
+[build]
+jobs = 4
+"#;
+        let aliases = parse_aliases(config).unwrap();
+
+        assert_eq!(
+            aliases,
+            vec![
+                ("b".to_string(), "build --release".to_string()),
+                ("xtask".to_string(), "run --package xtask --".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_config_without_aliases() {
+        let aliases = parse_aliases("[build]\njobs = 4\n").unwrap();
+
+        assert!(aliases.is_empty());
+    }
+
+    #[test]
+    fn test_find_xtask_commands() {
+        let source = r#"
+fn main() -> Result<()> {
+    let task = env::args().nth(1);
+    match task.as_deref() {
+        Some("dist") => dist()?,
+        Some("codegen") | Some("gen") => codegen()?,
+        _ => print_help(),
+    }
+    match flag {
+        "--verbose" => {}
+        "install-hooks" => install()?,
+    }
+    Ok(())
+}
+"#;
+
+        assert_eq!(
+            find_xtask_commands(source),
+            vec![
+                ("dist".to_string(), 5),
+                ("codegen".to_string(), 6),
+                ("gen".to_string(), 6),
+                ("install-hooks".to_string(), 11),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_manifest() {
+        let content = r#"
+[package]
+name = "app"
+version = "0.1.0"
+
+[[bin]]
+name = "server"
+path = "src/server.rs"
+
+[workspace]
+members = ["crates/*", "xtask"]
+exclude = ["crates/legacy"]
+"#;
+        let manifest = parse_manifest(content).unwrap();
+
+        assert_eq!(manifest.package.unwrap().name, "app");
+        assert_eq!(manifest.bin[0].name.as_deref(), Some("server"));
+
+        let workspace = manifest.workspace.unwrap();
+        assert_eq!(workspace.members, vec!["crates/*", "xtask"]);
+        assert_eq!(workspace.exclude, vec!["crates/legacy"]);
+    }
+
+    #[test]
+    fn test_package_bins() {
+        let dir = std::env::temp_dir().join(format!("rt-cargo-test-{}", std::process::id()));
+        fs::create_dir_all(dir.join("src/bin/multi")).unwrap();
+        fs::write(dir.join("src/main.rs"), "fn main() {}").unwrap();
+        fs::write(dir.join("src/bin/tool.rs"), "fn main() {}").unwrap();
+        fs::write(dir.join("src/bin/multi/main.rs"), "fn main() {}").unwrap();
+        fs::write(dir.join("src/bin/notes.txt"), "").unwrap();
+
+        let manifest = parse_manifest(
+            "[package]\nname = \"app\"\n\n[[bin]]\nname = \"server\"\npath = \"src/server.rs\"\n",
+        )
+        .unwrap();
+        let bins = package_bins(&dir, &manifest);
+
+        let no_autobins = parse_manifest("[package]\nname = \"app\"\nautobins = false\n").unwrap();
+        let explicit_only = package_bins(&dir, &no_autobins);
+
+        fs::remove_dir_all(&dir).ok();
+
+        assert_eq!(bins, vec!["server", "app", "multi", "tool"]);
+        assert!(explicit_only.is_empty());
+    }
+
+    #[test]
+    fn test_runner_name() {
+        let runner = CargoRunner::new();
+        assert_eq!(runner.name(), "Cargo.toml");
+    }
+}
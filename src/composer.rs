@@ -1,11 +1,11 @@
-use super::runner::Runner;
+use super::runner::{find_key_line, Runner, Task};
 use anyhow::{bail, Context, Result};
 use serde_json::Value;
 use std::process::Command;
 use std::{fs, io::ErrorKind};
 
 pub struct ComposerRunner {
-    tasks: Vec<String>,
+    tasks: Vec<Task>,
 }
 
 impl ComposerRunner {
@@ -13,9 +13,9 @@ impl ComposerRunner {
         return ComposerRunner { tasks: Vec::new() };
     }
 
-    fn read_composer_json() -> Result<Vec<String>> {
+    fn read_composer_json() -> Result<Vec<Task>> {
         let content = fs::read_to_string("composer.json");
-        let mut script_names: Vec<String> = Vec::new();
+        let mut script_names: Vec<Task> = Vec::new();
 
         let content = match content {
             Ok(content) => content,
@@ -35,8 +35,15 @@ impl ComposerRunner {
         };
 
         for (key, value) in scripts.iter() {
-            if let Value::String(_) = value {
-                script_names.push(key.to_string());
+            if let Value::String(command) = value {
+                let mut task = Task::new(key);
+                task.command = Some(command.to_string());
+                task.description = json["scripts-descriptions"][key]
+                    .as_str()
+                    .map(|d| d.to_string());
+                task.source = Some("composer.json".into());
+                task.line = find_key_line(&content, &["scripts", key]);
+                script_names.push(task);
             }
         }
 
@@ -49,10 +56,14 @@ impl Runner for ComposerRunner {
         return "composer.json";
     }
 
-    fn tasks(&self) -> &Vec<String> {
+    fn tasks(&self) -> &Vec<Task> {
         return &self.tasks;
     }
 
+    fn tasks_mut(&mut self) -> &mut Vec<Task> {
+        return &mut self.tasks;
+    }
+
     fn load(&mut self) -> Result<()> {
         let scripts =
             ComposerRunner::read_composer_json().context("Failed to read composer.json")?;
@@ -60,9 +71,9 @@ impl Runner for ComposerRunner {
         return Ok(());
     }
 
-    fn run(&self, task: &str, args: &[String]) -> Result<i32> {
-        eprintln!("[rt] Using composer");
+    fn command(&self, task: &str, args: &[String]) -> Result<Command> {
         let mut composer = Command::new("composer");
-        return self.execute(composer.arg(task).arg("--").args(args));
+        composer.arg(task).arg("--").args(args);
+        return Ok(composer);
     }
 }
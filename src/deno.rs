@@ -0,0 +1,241 @@
+use super::runner::{find_key_line, Runner, Task};
+use anyhow::{Context, Result};
+use serde_json::Value;
+use std::path::Path;
+use std::process::Command;
+use std::{fs, io::ErrorKind};
+
+const DENO_CONFIG_NAMES: [&str; 2] = ["deno.json", "deno.jsonc"];
+
+/// Remove comments and trailing commas so JSONC can be parsed as plain JSON
+fn strip_jsonc(content: &str) -> String {
+    let chars: Vec<char> = content.chars().collect();
+    let mut without_comments: Vec<char> = Vec::with_capacity(chars.len());
+    let mut index = 0;
+    let mut in_string = false;
+
+    while index < chars.len() {
+        let a_char = chars[index];
+        let next = chars.get(index + 1).copied();
+
+        if in_string {
+            without_comments.push(a_char);
+            if a_char == '\\' {
+                if let Some(escaped) = next {
+                    without_comments.push(escaped);
+                    index += 1;
+                }
+            } else if a_char == '"' {
+                in_string = false;
+            }
+            index += 1;
+            continue;
+        }
+
+        match (a_char, next) {
+            ('/', Some('/')) => {
+                while index < chars.len() && chars[index] != '\n' {
+                    index += 1;
+                }
+            }
+            ('/', Some('*')) => {
+                index += 2;
+                while index < chars.len()
+                    && !(chars[index] == '*' && chars.get(index + 1) == Some(&'/'))
+                {
+                    index += 1;
+                }
+                index += 2;
+            }
+            _ => {
+                in_string = a_char == '"';
+                without_comments.push(a_char);
+                index += 1;
+            }
+        }
+    }
+
+    let mut out = String::with_capacity(without_comments.len());
+    let mut escaped = false;
+    in_string = false;
+
+    for (index, a_char) in without_comments.iter().enumerate() {
+        if in_string {
+            if escaped {
+                escaped = false;
+            } else if *a_char == '\\' {
+                escaped = true;
+            } else if *a_char == '"' {
+                in_string = false;
+            }
+        } else if *a_char == '"' {
+            in_string = true;
+        } else if *a_char == ',' {
+            let closes = without_comments[index + 1..]
+                .iter()
+                .find(|c| !c.is_whitespace())
+                .is_some_and(|c| *c == '}' || *c == ']');
+
+            if closes {
+                continue;
+            }
+        }
+
+        out.push(*a_char);
+    }
+
+    return out;
+}
+
+fn parse_deno_tasks(content: &str) -> Result<Vec<Task>> {
+    let json: Value =
+        serde_json::from_str(&strip_jsonc(content)).context("Failed to parse JSON")?;
+    let mut tasks: Vec<Task> = Vec::new();
+
+    let Some(task_map) = json["tasks"].as_object() else {
+        return Ok(tasks);
+    };
+
+    for (key, value) in task_map.iter() {
+        let mut task = Task::new(key);
+
+        match value {
+            Value::String(command) => task.command = Some(command.to_string()),
+            // Deno 2 object form: { "command": "...", "description": "..." }
+            Value::Object(definition) => {
+                task.description = definition
+                    .get("description")
+                    .and_then(|d| d.as_str())
+                    .map(|d| d.to_string());
+                task.command = definition
+                    .get("command")
+                    .and_then(|c| c.as_str())
+                    .map(|c| c.to_string());
+            }
+            _ => continue,
+        }
+
+        task.line = find_key_line(content, &["tasks", key]);
+        tasks.push(task);
+    }
+
+    return Ok(tasks);
+}
+
+pub struct DenoRunner {
+    tasks: Vec<Task>,
+}
+
+impl DenoRunner {
+    pub fn new() -> Self {
+        return DenoRunner { tasks: Vec::new() };
+    }
+
+    fn read_deno_json() -> Result<Vec<Task>> {
+        let Some(path) = DENO_CONFIG_NAMES
+            .iter()
+            .find(|name| Path::new(name).exists())
+        else {
+            return Ok(Vec::new());
+        };
+
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if ErrorKind::NotFound == e.kind() => return Ok(Vec::new()),
+            Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path)),
+        };
+
+        let mut tasks =
+            parse_deno_tasks(&content).with_context(|| format!("Failed to parse {}", path))?;
+        for task in tasks.iter_mut() {
+            task.source = Some(path.into());
+        }
+
+        return Ok(tasks);
+    }
+}
+
+impl Runner for DenoRunner {
+    fn name(&self) -> &'static str {
+        return "deno.json";
+    }
+
+    fn marker_files(&self) -> Vec<&str> {
+        return DENO_CONFIG_NAMES.to_vec();
+    }
+
+    fn tasks(&self) -> &Vec<Task> {
+        return &self.tasks;
+    }
+
+    fn tasks_mut(&mut self) -> &mut Vec<Task> {
+        return &mut self.tasks;
+    }
+
+    fn load(&mut self) -> Result<()> {
+        self.tasks = DenoRunner::read_deno_json().context("Failed to read deno.json")?;
+        return Ok(());
+    }
+
+    fn command(&self, task: &str, args: &[String]) -> Result<Command> {
+        let mut deno = Command::new("deno");
+        deno.arg("task").arg(task).args(args);
+        return Ok(deno);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_jsonc() {
+        let content = r#"{
+  // line comment
+  "a": "http://not-a-comment", /* block
+  comment */
+  "b": [1, 2,],
+  "c": "quote \" // still string",
+  "d": "backslash \\",
+}"#;
+        let json: Value = serde_json::from_str(&strip_jsonc(content)).unwrap();
+
+        assert_eq!(json["a"], "http://not-a-comment");
+        assert_eq!(json["b"], serde_json::json!([1, 2]));
+        assert_eq!(json["c"], "quote \" // still string");
+        assert_eq!(json["d"], "backslash \\");
+    }
+
+    #[test]
+    fn test_parse_tasks() {
+        let content = r#"{
+  "tasks": {
+    "dev": "deno run --watch main.ts",
+    // Deno 2 style
+    "build": {
+      "description": "Build the app",
+      "command": "deno compile main.ts",
+      "dependencies": ["check"]
+    },
+    "check": "deno check main.ts",
+  }
+}"#;
+        let tasks = parse_deno_tasks(content).unwrap();
+        let names: Vec<&str> = tasks.iter().map(|t| t.name.as_str()).collect();
+
+        assert_eq!(names, vec!["build", "check", "dev"]);
+        assert_eq!(tasks[0].description.as_deref(), Some("Build the app"));
+        assert_eq!(tasks[0].command.as_deref(), Some("deno compile main.ts"));
+        assert_eq!(tasks[0].line, Some(5));
+        assert_eq!(tasks[1].description, None);
+        assert_eq!(tasks[1].command.as_deref(), Some("deno check main.ts"));
+        assert_eq!(tasks[2].line, Some(3));
+    }
+
+    #[test]
+    fn test_parse_without_tasks() {
+        let tasks = parse_deno_tasks(r#"{ "imports": {} }"#).unwrap();
+
+        assert!(tasks.is_empty());
+    }
+}
@@ -40,6 +40,33 @@ impl EnvFile {
     }
 }
 
+/// Replace the variable in env file contents or append it when missing
+pub fn set_var(contents: &str, key: &str, value: &str) -> String {
+    let mut found = false;
+    let mut lines: Vec<String> = Vec::new();
+
+    for line in contents.lines() {
+        // Keeps a leading `export ` of the existing line
+        let name = line.split_once('=').map_or("", |(k, _)| k.trim());
+        let is_key = name.trim_start_matches("export ") == key;
+
+        if is_key && !found {
+            found = true;
+            lines.push(format!("{}={}", name, value));
+        } else if !is_key {
+            lines.push(line.to_string());
+        }
+    }
+
+    if !found {
+        lines.push(format!("{}={}", key, value));
+    }
+
+    let mut out = lines.join("\n");
+    out.push('\n');
+    return out;
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -68,4 +95,15 @@ mod tests {
         assert_eq!(env_file.get("KEY1"), Some("value1 foo"));
         assert_eq!(env_file.get("KEY2"), Some("value2 bar"));
     }
+
+    #[test]
+    fn test_set_var() {
+        assert_eq!(set_var("", "KEY", "a"), "KEY=a\n");
+        assert_eq!(
+            set_var("OTHER=1\nexport KEY=old\n", "KEY", "new"),
+            "OTHER=1\nexport KEY=new\n"
+        );
+        assert_eq!(set_var("KEY=old\n", "KEY", "new"), "KEY=new\n");
+        assert_eq!(set_var("OTHER=1", "KEY", "a"), "OTHER=1\nKEY=a\n");
+    }
 }
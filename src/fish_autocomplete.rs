@@ -0,0 +1,93 @@
+use crate::runner::Runner;
+use crate::zsh_autocomplete::{get_completion_items, task_summary, CompletionItems};
+
+/// Tabs and newlines would break the `name\tdescription` line format
+fn fish_escape(text: &str) -> String {
+    return text.replace(['\t', '\n'], " ");
+}
+
+fn get_fish_completion_code(items: &CompletionItems) -> String {
+    let mut out = String::new();
+
+    match items {
+        CompletionItems::Files => {}
+        CompletionItems::Tasks(tasks) => {
+            for (name, task) in tasks {
+                out.push_str(&format!(
+                    "{}\t{}\n",
+                    fish_escape(&task.name),
+                    fish_escape(&task_summary(name, task))
+                ));
+            }
+        }
+        CompletionItems::Args(args) => {
+            for (value, description) in args {
+                out.push_str(&format!(
+                    "{}\t{}\n",
+                    fish_escape(value),
+                    fish_escape(description)
+                ));
+            }
+        }
+    }
+
+    return out;
+}
+
+pub fn get_fish_autocompletion(runners: &Vec<Box<dyn Runner>>, buffer: &str) -> String {
+    let items = get_completion_items(runners, buffer, "");
+    return get_fish_completion_code(&items);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runner::Task;
+    use crate::test_runner::{deploy_task, TestRunner};
+
+    fn runners() -> Vec<Box<dyn Runner>> {
+        let mut runner = TestRunner::new("runner1".to_string(), vec!["foo".to_string()]);
+        runner.tasks.push(deploy_task());
+        let mut build = Task::new("build");
+        build.description = Some("Build\tthe app".to_string());
+        runner.tasks.push(build);
+
+        return vec![Box::new(runner)];
+    }
+
+    #[test]
+    fn test_task_lines() {
+        assert_eq!(
+            get_fish_autocompletion(&runners(), "rt f"),
+            "foo\tfrom runner1\ndeploy\tfrom runner1\nbuild\tBuild the app\n"
+        );
+    }
+
+    #[test]
+    fn test_args_and_files() {
+        assert_eq!(
+            get_fish_autocompletion(&runners(), "rt deploy "),
+            "prod\tenv\n"
+        );
+        assert_eq!(get_fish_autocompletion(&runners(), "rt foo "), "");
+    }
+
+    #[test]
+    fn test_combined_with_other_commands() {
+        let runners = runners();
+
+        for buffer in [
+            "ls && rt fo",
+            "ls && ls && rt fo",
+            "ls&&rt fo",
+            "ls;rt fo",
+            "ls; rt fo",
+            "ls ; rt fo",
+        ] {
+            let lines = get_fish_autocompletion(&runners, buffer);
+            assert_eq!(lines.lines().count(), 3, "{}", buffer);
+        }
+
+        assert_eq!(get_fish_autocompletion(&runners, "rt foo && rt foo "), "");
+    }
+}
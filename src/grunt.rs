@@ -0,0 +1,265 @@
+use anyhow::Result;
+use std::borrow::Borrow;
+use std::path::Path;
+use std::process::Command;
+use swc_common::SourceMap;
+use swc_ecma_ast::{
+    AssignTarget, Expr, ExprOrSpread, Lit, MemberProp, Module, ModuleItem, Pat, SimpleAssignTarget,
+    Stmt,
+};
+
+use super::npm;
+use super::runner::{Runner, Task};
+use super::swc::{function_body, member_call, parse_as_swc_module, string_arg};
+
+const GRUNTFILE_NAMES: [&str; 2] = ["Gruntfile.js", "gruntfile.js"];
+
+/// Name of the first parameter of a function, eg. `grunt`
+fn first_param(expr: &Expr) -> Option<String> {
+    let pat = match expr {
+        Expr::Fn(function) => &function.function.params.first()?.pat,
+        Expr::Arrow(arrow) => arrow.params.first()?,
+        _ => return None,
+    };
+
+    let Pat::Ident(ident) = pat else {
+        return None;
+    };
+    return Some(ident.id.sym.to_string());
+}
+
+/// The grunt parameter and the body of `module.exports = function (grunt) {}`
+fn exported_function(module: &Module) -> Option<(String, &[Stmt])> {
+    for item in module.body.iter() {
+        let ModuleItem::Stmt(Stmt::Expr(expr)) = item else {
+            continue;
+        };
+        let Expr::Assign(assign) = expr.expr.borrow() else {
+            continue;
+        };
+        let AssignTarget::Simple(SimpleAssignTarget::Member(member)) = &assign.left else {
+            continue;
+        };
+
+        let is_module_exports = match (member.obj.borrow(), &member.prop) {
+            (Expr::Ident(object), MemberProp::Ident(property)) => {
+                object.sym == "module" && property.sym == "exports"
+            }
+            _ => false,
+        };
+
+        if !is_module_exports {
+            continue;
+        }
+
+        if let (Some(grunt), Some(body)) =
+            (first_param(&assign.right), function_body(&assign.right))
+        {
+            return Some((grunt, body));
+        }
+    }
+
+    return None;
+}
+
+/// Aliased tasks like `grunt.registerTask("default", ["jshint", "uglify"])`
+fn task_list(args: &[ExprOrSpread]) -> Vec<String> {
+    let array = args.iter().skip(1).find_map(|arg| match arg.expr.borrow() {
+        Expr::Array(array) => Some(array),
+        _ => None,
+    });
+
+    let Some(array) = array else {
+        return Vec::new();
+    };
+
+    return array
+        .elems
+        .iter()
+        .flatten()
+        .filter_map(|elem| match elem.expr.borrow() {
+            Expr::Lit(Lit::Str(task)) => Some(task.value.to_string()),
+            _ => None,
+        })
+        .collect();
+}
+
+/// Tasks registered with `grunt.registerTask()` and
+/// `grunt.registerMultiTask()` in the exported function
+fn get_registered_tasks(module: &Module, cm: &SourceMap) -> Vec<Task> {
+    let mut tasks: Vec<Task> = Vec::new();
+
+    let Some((grunt, body)) = exported_function(module) else {
+        return tasks;
+    };
+
+    for stmt in body {
+        let Stmt::Expr(expr) = stmt else {
+            continue;
+        };
+        let Expr::Call(call) = expr.expr.borrow() else {
+            continue;
+        };
+
+        let is_register = member_call(&call.callee).is_some_and(|(object, method)| {
+            object == grunt && (method == "registerTask" || method == "registerMultiTask")
+        });
+
+        if !is_register {
+            continue;
+        }
+
+        let Some(name) = string_arg(&call.args, 0) else {
+            continue;
+        };
+
+        let mut task = Task::new(name);
+        task.description = string_arg(&call.args, 1);
+        task.deps = task_list(&call.args);
+        task.line = Some(cm.lookup_char_pos(call.span.lo).line);
+        tasks.push(task);
+    }
+
+    return tasks;
+}
+
+/// Arguments for grunt running the task. Task arguments are passed like
+/// `deploy:prod:eu` and options as is.
+fn grunt_args(task: &str, args: &[String]) -> Vec<String> {
+    let (options, task_args): (Vec<&String>, Vec<&String>) =
+        args.iter().partition(|arg| arg.starts_with('-'));
+
+    let mut target = task.to_string();
+    for arg in task_args {
+        target.push(':');
+        target.push_str(arg);
+    }
+
+    return std::iter::once(target)
+        .chain(options.into_iter().cloned())
+        .collect();
+}
+
+pub struct GruntRunner {
+    tasks: Vec<Task>,
+}
+
+impl GruntRunner {
+    pub fn new() -> Self {
+        return GruntRunner { tasks: Vec::new() };
+    }
+}
+
+impl Runner for GruntRunner {
+    fn name(&self) -> &'static str {
+        return "Gruntfile";
+    }
+
+    fn marker_files(&self) -> Vec<&str> {
+        return GRUNTFILE_NAMES.to_vec();
+    }
+
+    fn tasks(&self) -> &Vec<Task> {
+        return &self.tasks;
+    }
+
+    fn tasks_mut(&mut self) -> &mut Vec<Task> {
+        return &mut self.tasks;
+    }
+
+    fn load(&mut self) -> Result<()> {
+        let Some(path) = GRUNTFILE_NAMES.iter().find(|name| Path::new(name).exists()) else {
+            return Ok(());
+        };
+
+        if let Some((cm, module)) = parse_as_swc_module(path)? {
+            self.tasks = get_registered_tasks(&module, &cm);
+            for task in self.tasks.iter_mut() {
+                task.source = Some(path.into());
+            }
+        }
+
+        return Ok(());
+    }
+
+    fn explain(&self, _task: &str) -> Vec<(String, String)> {
+        return vec![("grunt".to_string(), npm::explain_bin("grunt"))];
+    }
+
+    fn command(&self, task: &str, args: &[String]) -> Result<Command> {
+        let mut grunt = npm::bin_command("grunt");
+        grunt.args(grunt_args(task, args));
+        return Ok(grunt);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::swc::parse_code;
+
+    fn parse_tasks(code: &str) -> Vec<Task> {
+        let (cm, module) = parse_code("Gruntfile.js", code);
+        return get_registered_tasks(&module, &cm);
+    }
+
+    #[test]
+    fn test_parse_registered_tasks() {
+        let code = r#"
+            module.exports = function (grunt) {
+                grunt.initConfig({ uglify: {} });
+                grunt.loadNpmTasks("grunt-contrib-uglify");
+
+                grunt.registerTask("default", ["jshint", "uglify"]);
+                grunt.registerTask("deploy", "Deploy the site", function (env) {});
+                grunt.registerMultiTask("copy", "Copy files", ["clean"], function () {});
+                other.registerTask("nope", function () {});
+
+                function nested() {
+                    grunt.registerTask("hidden", function () {});
+                }
+            };
+        "#;
+        let tasks = parse_tasks(code);
+        let names: Vec<&str> = tasks.iter().map(|task| task.name.as_str()).collect();
+
+        assert_eq!(names, vec!["default", "deploy", "copy"]);
+        assert_eq!(tasks[0].deps, vec!["jshint", "uglify"]);
+        assert_eq!(tasks[0].description, None);
+        assert_eq!(tasks[1].description.as_deref(), Some("Deploy the site"));
+        assert_eq!(tasks[1].line, Some(7));
+        assert_eq!(tasks[2].deps, vec!["clean"]);
+    }
+
+    #[test]
+    fn test_parse_arrow_function() {
+        let code = r#"
+            module.exports = (g) => {
+                g.registerTask("build", () => {});
+            };
+        "#;
+        let tasks = parse_tasks(code);
+
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].name, "build");
+    }
+
+    #[test]
+    fn test_parse_without_export() {
+        let code = r#"grunt.registerTask("build", () => {});"#;
+
+        assert!(parse_tasks(code).is_empty());
+    }
+
+    #[test]
+    fn test_grunt_args() {
+        let args = |args: &[&str]| -> Vec<String> {
+            let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+            return grunt_args("deploy", &args);
+        };
+
+        assert_eq!(args(&[]), vec!["deploy"]);
+        assert_eq!(args(&["prod", "eu"]), vec!["deploy:prod:eu"]);
+        assert_eq!(args(&["prod", "--force"]), vec!["deploy:prod", "--force"]);
+    }
+}
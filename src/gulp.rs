@@ -0,0 +1,523 @@
+use anyhow::{bail, Result};
+use std::borrow::Borrow;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::process::Command;
+use swc_common::{SourceMap, Span, Spanned};
+use swc_ecma_ast::{
+    AssignTarget, Decl, DefaultDecl, ExportSpecifier, Expr, ExprOrSpread, Lit, MemberExpr,
+    MemberProp, Module, ModuleDecl, ModuleItem, Pat, Prop, PropName, PropOrSpread,
+    SimpleAssignTarget, Stmt,
+};
+
+use super::npm;
+use super::runner::{Runner, Task};
+use super::swc::{parse_as_swc_module, string_arg, ModuleBindings};
+
+/// Gulpfile names in the order gulp looks for them
+const GULPFILE_NAMES: [&str; 5] = [
+    "gulpfile.js",
+    "Gulpfile.js",
+    "gulpfile.cjs",
+    "gulpfile.mjs",
+    "gulpfile.ts",
+];
+
+/// Is the expression a function or gulp's `series(...)` or `parallel(...)`
+fn is_function_value(expr: &Expr, bindings: &ModuleBindings) -> bool {
+    return match expr {
+        Expr::Fn(_) | Expr::Arrow(_) => true,
+        Expr::Call(call) => matches!(
+            bindings.callee_name(&call.callee),
+            Some("series") | Some("parallel")
+        ),
+        _ => false,
+    };
+}
+
+/// Names of the functions declared at the top level of the module, also
+/// variables holding a function or a `series(...)`
+fn declared_functions(module: &Module, bindings: &ModuleBindings) -> HashSet<String> {
+    let mut functions: HashSet<String> = HashSet::new();
+
+    for item in module.body.iter() {
+        let decl = match item {
+            ModuleItem::Stmt(Stmt::Decl(decl)) => decl,
+            ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(export)) => &export.decl,
+            _ => continue,
+        };
+
+        match decl {
+            Decl::Fn(function) => {
+                functions.insert(function.ident.sym.to_string());
+            }
+            Decl::Var(var) => {
+                for declarator in &var.decls {
+                    let Pat::Ident(ident) = &declarator.name else {
+                        continue;
+                    };
+                    let is_function = declarator
+                        .init
+                        .as_deref()
+                        .is_some_and(|init| is_function_value(init, bindings));
+
+                    if is_function {
+                        functions.insert(ident.id.sym.to_string());
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    return functions;
+}
+
+/// Variable name of the task function, used to find its description
+fn local_name(expr: &Expr) -> Option<String> {
+    return match expr {
+        Expr::Ident(ident) => Some(ident.sym.to_string()),
+        Expr::Fn(function) => function.ident.as_ref().map(|ident| ident.sym.to_string()),
+        _ => None,
+    };
+}
+
+/// Names of a member expression like `module.exports.build`
+fn member_names(member: &MemberExpr) -> Option<Vec<&str>> {
+    let mut names = match member.obj.borrow() {
+        Expr::Ident(ident) => vec![&*ident.sym],
+        Expr::Member(object) => member_names(object)?,
+        _ => return None,
+    };
+
+    let MemberProp::Ident(property) = &member.prop else {
+        return None;
+    };
+    names.push(&property.sym);
+
+    return Some(names);
+}
+
+/// Finds the exported functions and `gulp.task()` calls
+struct GulpTasks<'a> {
+    cm: &'a SourceMap,
+    bindings: ModuleBindings,
+    /// From `declared_functions`
+    functions: HashSet<String>,
+    tasks: Vec<Task>,
+    /// Variable names of the task functions by task name
+    locals: HashMap<String, String>,
+    /// From `build.description = "..."` by variable name
+    descriptions: HashMap<String, String>,
+}
+
+impl GulpTasks<'_> {
+    /// Can an exported value be a task: a function, `series(...)` or the
+    /// name of a function declared in the module but not an object, a
+    /// string or any other call like `require("./config")`
+    fn is_task_value(&self, expr: &Expr) -> bool {
+        if let Expr::Ident(ident) = expr {
+            return self.functions.contains(&*ident.sym);
+        }
+
+        return is_function_value(expr, &self.bindings);
+    }
+
+    fn add(&mut self, name: String, local: Option<String>, span: Span) {
+        if self.tasks.iter().any(|task| task.name == name) {
+            return;
+        }
+
+        if let Some(local) = local {
+            self.locals.insert(name.clone(), local);
+        }
+
+        let mut task = Task::new(name);
+        task.line = Some(self.cm.lookup_char_pos(span.lo).line);
+        self.tasks.push(task);
+    }
+
+    fn visit_module_decl(&mut self, decl: &ModuleDecl) {
+        match decl {
+            // export function build() {}
+            ModuleDecl::ExportDecl(export) => match &export.decl {
+                Decl::Fn(function) => {
+                    let name = function.ident.sym.to_string();
+                    self.add(name.clone(), Some(name), export.span);
+                }
+                Decl::Var(var) => {
+                    for declarator in &var.decls {
+                        let Pat::Ident(ident) = &declarator.name else {
+                            continue;
+                        };
+                        let is_task = declarator
+                            .init
+                            .as_deref()
+                            .is_some_and(|init| self.is_task_value(init));
+
+                        if is_task {
+                            let name = ident.id.sym.to_string();
+                            self.add(name.clone(), Some(name), declarator.span);
+                        }
+                    }
+                }
+                _ => {}
+            },
+            // export default function build() {}
+            ModuleDecl::ExportDefaultDecl(export) => {
+                if let DefaultDecl::Fn(function) = &export.decl {
+                    let local = function.ident.as_ref().map(|ident| ident.sym.to_string());
+                    self.add("default".to_string(), local, export.span);
+                }
+            }
+            // export default series(clean, build)
+            ModuleDecl::ExportDefaultExpr(export) => {
+                if self.is_task_value(&export.expr) {
+                    self.add("default".to_string(), local_name(&export.expr), export.span);
+                }
+            }
+            // export { build, clean as cleanTask }
+            ModuleDecl::ExportNamed(export) if export.src.is_none() => {
+                for specifier in &export.specifiers {
+                    let ExportSpecifier::Named(named) = specifier else {
+                        continue;
+                    };
+                    let local = named.orig.atom().to_string();
+                    if !self.functions.contains(&local) {
+                        continue;
+                    }
+                    let name = match &named.exported {
+                        Some(exported) => exported.atom().to_string(),
+                        None => local.clone(),
+                    };
+                    self.add(name, Some(local), named.span);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn visit_expr(&mut self, expr: &Expr) {
+        match expr {
+            Expr::Assign(assign) => {
+                let AssignTarget::Simple(SimpleAssignTarget::Member(member)) = &assign.left else {
+                    return;
+                };
+                let Some(names) = member_names(member) else {
+                    return;
+                };
+
+                match names.as_slice() {
+                    // build.description = "Build it"
+                    [local, "description"] => {
+                        if let Expr::Lit(Lit::Str(description)) = assign.right.borrow() {
+                            self.descriptions
+                                .insert(local.to_string(), description.value.to_string());
+                        }
+                    }
+                    // module.exports = { build, clean: cleanTask }
+                    ["module", "exports"] => {
+                        if let Expr::Object(object) = assign.right.borrow() {
+                            self.visit_exports_object(&object.props);
+                        }
+                    }
+                    // exports.build = build
+                    ["exports", name] | ["module", "exports", name] => {
+                        if self.is_task_value(&assign.right) {
+                            self.add(name.to_string(), local_name(&assign.right), assign.span);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            // gulp.task("build", build)
+            Expr::Call(call) if self.bindings.callee_name(&call.callee) == Some("task") => {
+                self.visit_task_call(&call.args, call.span);
+            }
+            _ => {}
+        }
+    }
+
+    fn visit_exports_object(&mut self, props: &[PropOrSpread]) {
+        for prop in props {
+            let PropOrSpread::Prop(prop) = prop else {
+                continue;
+            };
+
+            match prop.borrow() {
+                Prop::Shorthand(ident) if self.functions.contains(ident.sym.as_str()) => {
+                    let name = ident.sym.to_string();
+                    self.add(name.clone(), Some(name), ident.span);
+                }
+                Prop::KeyValue(key_value) if self.is_task_value(&key_value.value) => {
+                    let name = match &key_value.key {
+                        PropName::Ident(ident) => ident.sym.to_string(),
+                        PropName::Str(string) => string.value.to_string(),
+                        _ => continue,
+                    };
+                    self.add(name, local_name(&key_value.value), key_value.value.span());
+                }
+                Prop::Method(method) => {
+                    if let PropName::Ident(ident) = &method.key {
+                        self.add(ident.sym.to_string(), None, ident.span);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn visit_task_call(&mut self, args: &[ExprOrSpread], span: Span) {
+        // task(build) registers the function with its name
+        if let Some(Expr::Ident(ident)) = args.first().map(|arg| arg.expr.borrow()) {
+            let name = ident.sym.to_string();
+            self.add(name.clone(), Some(name), span);
+            return;
+        }
+
+        // A single argument gets a registered task
+        let Some(name) = string_arg(args, 0).filter(|_| args.len() > 1) else {
+            return;
+        };
+
+        let local = args.last().and_then(|arg| local_name(&arg.expr));
+        self.add(name.clone(), local, span);
+
+        // Gulp 3 dependencies like gulp.task("build", ["clean"], fn)
+        if let Some(Expr::Array(array)) = args.get(1).map(|arg| arg.expr.borrow()) {
+            let deps = array
+                .elems
+                .iter()
+                .flatten()
+                .filter_map(|elem| match elem.expr.borrow() {
+                    Expr::Lit(Lit::Str(dep)) => Some(dep.value.to_string()),
+                    _ => None,
+                });
+
+            if let Some(task) = self.tasks.iter_mut().find(|task| task.name == name) {
+                task.deps = deps.collect();
+            }
+        }
+    }
+}
+
+fn get_gulp_tasks(module: &Module, cm: &SourceMap) -> Vec<Task> {
+    let bindings = ModuleBindings::from_module(module, "gulp", false);
+    let mut collector = GulpTasks {
+        cm,
+        functions: declared_functions(module, &bindings),
+        bindings,
+        tasks: Vec::new(),
+        locals: HashMap::new(),
+        descriptions: HashMap::new(),
+    };
+
+    for item in module.body.iter() {
+        match item {
+            ModuleItem::ModuleDecl(decl) => collector.visit_module_decl(decl),
+            ModuleItem::Stmt(Stmt::Expr(expr)) => collector.visit_expr(&expr.expr),
+            _ => {}
+        }
+    }
+
+    let GulpTasks {
+        mut tasks,
+        locals,
+        descriptions,
+        ..
+    } = collector;
+
+    for task in tasks.iter_mut() {
+        task.description = locals
+            .get(&task.name)
+            .and_then(|local| descriptions.get(local))
+            .cloned();
+    }
+
+    return tasks;
+}
+
+/// Arguments for gulp running the task. Gulp runs every other word as a
+/// task so only options can be passed.
+fn gulp_args(task: &str, args: &[String]) -> Result<Vec<String>> {
+    if let Some(arg) = args.iter().find(|arg| !arg.starts_with('-')) {
+        bail!(
+            "Gulp tasks take no arguments, '{}' would run as another task. Pass options like --env={} instead.",
+            arg,
+            arg
+        );
+    }
+
+    return Ok(std::iter::once(task.to_string())
+        .chain(args.iter().cloned())
+        .collect());
+}
+
+pub struct GulpRunner {
+    tasks: Vec<Task>,
+}
+
+impl GulpRunner {
+    pub fn new() -> Self {
+        return GulpRunner { tasks: Vec::new() };
+    }
+}
+
+impl Runner for GulpRunner {
+    fn name(&self) -> &'static str {
+        return "gulpfile";
+    }
+
+    fn marker_files(&self) -> Vec<&str> {
+        return GULPFILE_NAMES.to_vec();
+    }
+
+    fn tasks(&self) -> &Vec<Task> {
+        return &self.tasks;
+    }
+
+    fn tasks_mut(&mut self) -> &mut Vec<Task> {
+        return &mut self.tasks;
+    }
+
+    fn load(&mut self) -> Result<()> {
+        let Some(path) = GULPFILE_NAMES.iter().find(|name| Path::new(name).exists()) else {
+            return Ok(());
+        };
+
+        if let Some((cm, module)) = parse_as_swc_module(path)? {
+            self.tasks = get_gulp_tasks(&module, &cm);
+            for task in self.tasks.iter_mut() {
+                task.source = Some(path.into());
+            }
+        }
+
+        return Ok(());
+    }
+
+    fn explain(&self, _task: &str) -> Vec<(String, String)> {
+        return vec![("gulp".to_string(), npm::explain_bin("gulp"))];
+    }
+
+    fn command(&self, task: &str, args: &[String]) -> Result<Command> {
+        let args = gulp_args(task, args)?;
+        let mut gulp = npm::bin_command("gulp");
+        gulp.args(args);
+        return Ok(gulp);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::swc::parse_code;
+
+    fn parse_tasks(path: &str, code: &str) -> Vec<Task> {
+        let (cm, module) = parse_code(path, code);
+        return get_gulp_tasks(&module, &cm);
+    }
+
+    fn names(tasks: &[Task]) -> Vec<&str> {
+        return tasks.iter().map(|task| task.name.as_str()).collect();
+    }
+
+    #[test]
+    fn test_parse_esm_exports() {
+        let code = r#"
+            import { series, src } from "gulp";
+
+            export function clean() {}
+            async function build() {}
+            build.description = "Build it";
+            const lint = () => {};
+            export const paths = { src: "src" };
+            export const ci = series(clean, build);
+            export const config = require("./config");
+            export const env = process.env.X || getEnv();
+            export const other = concat(clean, build);
+            export { build, lint as check, paths as settings };
+            export default series(clean, build);
+        "#;
+        let tasks = parse_tasks("gulpfile.ts", code);
+
+        assert_eq!(
+            names(&tasks),
+            vec!["clean", "ci", "build", "check", "default"]
+        );
+        assert_eq!(tasks[0].line, Some(4));
+        assert_eq!(tasks[2].description.as_deref(), Some("Build it"));
+    }
+
+    #[test]
+    fn test_parse_commonjs_exports() {
+        let code = r#"
+            const { series } = require("gulp");
+            const paths = { css: "src/*.css" };
+            const watchFiles = () => {};
+            const build = series(css);
+
+            function css() {}
+            function html() {}
+            css.description = "Compile CSS";
+            exports.css = css;
+            exports.paths = paths;
+            exports.missing = missing;
+            exports.default = series(css);
+            module.exports.js = function js() {};
+            module.exports.settings = require("./settings");
+            module.exports = { html, paths, watch: watchFiles, build: build, config: {} };
+        "#;
+        let tasks = parse_tasks("gulpfile.js", code);
+
+        assert_eq!(
+            names(&tasks),
+            vec!["css", "default", "js", "html", "watch", "build"]
+        );
+        assert_eq!(tasks[0].description.as_deref(), Some("Compile CSS"));
+    }
+
+    #[test]
+    fn test_gulp_args() {
+        let args = |args: &[&str]| {
+            let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+            return gulp_args("build", &args);
+        };
+
+        assert_eq!(args(&[]).unwrap(), vec!["build"]);
+        assert_eq!(
+            args(&["--production", "--env=ci"]).unwrap(),
+            vec!["build", "--production", "--env=ci"]
+        );
+        assert!(args(&["--production", "clean"]).is_err());
+    }
+
+    #[test]
+    fn test_command_rejects_positional_args() {
+        let error = GulpRunner::new()
+            .command("build", &["clean".to_string()])
+            .unwrap_err();
+
+        assert!(error
+            .to_string()
+            .contains("'clean' would run as another task"));
+    }
+
+    #[test]
+    fn test_parse_task_calls() {
+        let code = r#"
+            var gulp = require("gulp");
+            const { task: defineTask } = require("gulp");
+
+            gulp.task("styles", ["clean"], function () {});
+            defineTask("scripts", scripts);
+            gulp.task(images);
+            gulp.task("styles");
+            other.task("nope", () => {});
+            task("global", () => {});
+        "#;
+        let tasks = parse_tasks("gulpfile.js", code);
+
+        assert_eq!(names(&tasks), vec!["styles", "scripts", "images"]);
+        assert_eq!(tasks[0].deps, vec!["clean"]);
+    }
+}
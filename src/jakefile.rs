@@ -1,111 +1,224 @@
-use anyhow::{anyhow, bail, Context, Result};
+use anyhow::Result;
 use std::borrow::Borrow;
-use std::io::ErrorKind;
 use std::path::Path;
 use std::process::Command;
-use swc_common::{sync::Lrc, SourceMap};
-use swc_ecma_ast::{Callee, Expr, Lit, Module, ModuleItem, Stmt};
-use swc_ecma_parser::{lexer::Lexer, Parser, StringInput, Syntax};
-
-use super::runner::Runner;
+use swc_common::SourceMap;
+use swc_ecma_ast::{BlockStmtOrExpr, Expr, ExprOrSpread, Lit, Module, ModuleItem, Pat, Stmt};
+
+use super::npm;
+use super::runner::{Runner, Task, TaskParam};
+use super::swc::{function_body, parse_as_swc_module, string_arg, ModuleBindings};
+
+/// Jakefile names in the order jake looks for them
+const JAKEFILE_NAMES: [&str; 5] = [
+    "Jakefile",
+    "jakefile.js",
+    "jakefile.cjs",
+    "jakefile.mjs",
+    "jakefile.ts",
+];
+
+/// Task parameter from an action function parameter like `env`,
+/// `env = "dev"` or `...rest`
+fn pat_to_param(pat: &Pat) -> Option<TaskParam> {
+    return match pat {
+        Pat::Ident(ident) => Some(TaskParam {
+            name: ident.id.sym.to_string(),
+            ..Default::default()
+        }),
+        Pat::Assign(assign) => {
+            let mut param = pat_to_param(&assign.left)?;
+            param.default = match assign.right.borrow() {
+                Expr::Lit(Lit::Str(value)) => Some(value.value.to_string()),
+                Expr::Lit(Lit::Num(value)) => Some(value.value.to_string()),
+                Expr::Lit(Lit::Bool(value)) => Some(value.value.to_string()),
+                _ => None,
+            };
+            Some(param)
+        }
+        Pat::Rest(rest) => {
+            let mut param = pat_to_param(&rest.arg)?;
+            param.variadic = true;
+            Some(param)
+        }
+        _ => None,
+    };
+}
 
-fn parse_as_swc_module(path: &str) -> Result<Option<Module>> {
-    let cm: Lrc<SourceMap> = Default::default();
+/// Parameters of the action function which jake calls with the arguments
+/// from `jake task[a,b]`
+fn action_params(args: &[ExprOrSpread]) -> Vec<TaskParam> {
+    let action = args
+        .iter()
+        .rev()
+        .map(|arg| arg.expr.borrow())
+        .find(|expr| matches!(expr, Expr::Fn(_) | Expr::Arrow(_)));
+
+    let pats: Vec<&Pat> = match action {
+        Some(Expr::Fn(function)) => function.function.params.iter().map(|p| &p.pat).collect(),
+        Some(Expr::Arrow(arrow)) => arrow.params.iter().collect(),
+        _ => return Vec::new(),
+    };
 
-    let maybe_file = cm.load_file(Path::new(path));
+    return pats.into_iter().filter_map(pat_to_param).collect();
+}
 
-    let fm = match maybe_file {
-        Ok(f) => f,
-        Err(e) => {
-            if ErrorKind::NotFound == e.kind() {
-                return Ok(None);
-            }
-            bail!("Failed to read {}: {}", path, e.to_string());
-        }
+/// Prerequisites from an array like `task("x", ["build", "lint"], fn)`
+fn task_deps(args: &[ExprOrSpread]) -> Vec<String> {
+    let Some(Expr::Array(array)) = args.get(1).map(|arg| arg.expr.borrow()) else {
+        return Vec::new();
     };
 
-    let lexer = Lexer::new(
-        // We want to parse ecmascript
-        Syntax::Es(Default::default()),
-        // EsVersion defaults to es5
-        Default::default(),
-        StringInput::from(&*fm),
-        None,
-    );
-
-    let mut parser = Parser::new_from(lexer);
-
-    return match parser.parse_module() {
-        Ok(m) => Ok(Some(m)),
-        Err(e) => {
-            return Err(anyhow!("Parse error {:?}", e))
-                .context(format!("Failed to parse {}", path));
-        }
-    };
+    return array
+        .elems
+        .iter()
+        .flatten()
+        .filter_map(|elem| match elem.expr.borrow() {
+            Expr::Lit(Lit::Str(dep)) => Some(dep.value.to_string()),
+            _ => None,
+        })
+        .collect();
 }
 
-fn get_task_fn_calls(module: &Module) -> Vec<String> {
-    let mut tasks: Vec<String> = Vec::new();
+/// Walks the top level statements and `namespace()` blocks. Functions are
+/// not entered as their tasks are only defined when they are called.
+struct TaskCollector<'a> {
+    cm: &'a SourceMap,
+    bindings: ModuleBindings,
+    namespaces: Vec<String>,
+    /// From `desc()`, used for the next task
+    description: Option<String>,
+    tasks: Vec<Task>,
+}
 
-    for item in module.body.iter() {
-        // Is statement, eg. not an export declaration etc.
-        let ModuleItem::Stmt(stmt) = item else {
-            continue;
-        };
+impl TaskCollector<'_> {
+    fn visit_stmts<'s>(&mut self, stmts: impl IntoIterator<Item = &'s Stmt>) {
+        for stmt in stmts {
+            // Is expression statament, eg. not a variable declaration etc.
+            if let Stmt::Expr(expr) = stmt {
+                self.visit_expr(&expr.expr);
+            }
+        }
+    }
 
-        // Is expression statament, eg. not a variable declaration etc.
-        let Stmt::Expr(expr) = stmt else {
-            continue;
+    fn visit_expr(&mut self, expr: &Expr) {
+        // Is regular function call, eg. not super() or dynamic import() call
+        let Expr::Call(call) = expr else {
+            return;
         };
 
-        // is call experssion
-        let Expr::Call(call) = expr.expr.borrow() else {
-            continue;
-        };
+        match self.bindings.callee_name(&call.callee) {
+            Some("desc") => self.description = string_arg(&call.args, 0),
+            Some("namespace") => self.visit_namespace(&call.args),
+            Some(kind @ ("task" | "file" | "directory" | "rule")) => {
+                // The name must be a string literal, eg. not task(ding)
+                let Some(name) = string_arg(&call.args, 0) else {
+                    return;
+                };
+
+                let mut task = Task::new(
+                    self.namespaces
+                        .iter()
+                        .chain(std::iter::once(&name))
+                        .cloned()
+                        .collect::<Vec<String>>()
+                        .join(":"),
+                );
+                task.description = self.description.take();
+                task.line = Some(self.cm.lookup_char_pos(call.span.lo).line);
+
+                if kind == "rule" {
+                    // Rules create tasks for matching names on demand so the
+                    // pattern itself is not listed
+                    task.hidden = true;
+                } else {
+                    task.deps = task_deps(&call.args);
+                    task.params = action_params(&call.args);
+                }
+
+                self.tasks.push(task);
+            }
+            _ => {}
+        }
+    }
 
-        // Is regular function call, eg. not super()  or dynamic import() call
-        let Callee::Expr(expr) = &call.callee else {
-            continue;
+    fn visit_namespace(&mut self, args: &[ExprOrSpread]) {
+        let Some(name) = string_arg(args, 0) else {
+            return;
         };
 
-        // get the caller identifier eg. "fn" from fn()
-        let Expr::Ident(ident) = expr.borrow() else {
-            continue;
-        };
+        self.namespaces.push(name);
 
-        // is task();
-        if ident.sym.to_string() != "task" {
-            continue;
+        let scope = args.get(1).map(|arg| arg.expr.borrow());
+
+        if let Some(body) = scope.and_then(function_body) {
+            self.visit_stmts(body);
+        } else if let Some(Expr::Arrow(arrow)) = scope {
+            if let BlockStmtOrExpr::Expr(expr) = arrow.body.borrow() {
+                self.visit_expr(expr);
+            }
         }
 
-        // get the first argument
-        let Some(arg) = call.args.get(0) else {
-            continue;
-        };
+        self.namespaces.pop();
+    }
+}
 
-        // The first must be a literal. Eg. not a task(ding)
-        let Expr::Lit(literal) = arg.expr.borrow() else {
-            continue;
-        };
+fn get_task_fn_calls(module: &Module, cm: &SourceMap) -> Vec<Task> {
+    let mut collector = TaskCollector {
+        cm,
+        // Jake defines its functions and the jake object as globals
+        bindings: ModuleBindings::from_module(module, "jake", true),
+        namespaces: Vec::new(),
+        description: None,
+        tasks: Vec::new(),
+    };
 
-        // It must the a string literal
-        let Lit::Str(string_literal) = literal else {
-            continue;
-        };
+    // Statements only, eg. not export declarations etc.
+    collector.visit_stmts(module.body.iter().filter_map(|item| match item {
+        ModuleItem::Stmt(stmt) => Some(stmt),
+        _ => None,
+    }));
+
+    return collector.tasks;
+}
+
+/// Arguments for jake running the task. Positional arguments go to the task
+/// like `deploy[prod,eu]`, `KEY=value` environment arguments and options
+/// are passed as is.
+fn jake_args(task: &str, args: &[String]) -> Vec<String> {
+    let is_env = |arg: &str| {
+        return arg.split_once('=').is_some_and(|(key, _)| {
+            !key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        });
+    };
+
+    let (passed, task_args): (Vec<&String>, Vec<&String>) = args
+        .iter()
+        .partition(|arg| arg.starts_with('-') || is_env(arg));
 
-        tasks.push(string_literal.value.to_string());
+    let mut jake_args: Vec<String> = passed.into_iter().cloned().collect();
+
+    if task_args.is_empty() {
+        jake_args.push(task.to_string());
+    } else {
+        let task_args: Vec<&str> = task_args.iter().map(|arg| arg.as_str()).collect();
+        jake_args.push(format!("{}[{}]", task, task_args.join(",")));
     }
 
-    return tasks;
+    return jake_args;
 }
 
 pub struct JakeRunner {
-    tasks: Vec<String>,
+    tasks: Vec<Task>,
+    jakefile: Option<&'static str>,
 }
 
 impl JakeRunner {
     pub fn new() -> Self {
-        return JakeRunner { tasks: Vec::new() };
+        return JakeRunner {
+            tasks: Vec::new(),
+            jakefile: None,
+        };
     }
 }
 
@@ -114,48 +227,75 @@ impl Runner for JakeRunner {
         return "jakefile";
     }
 
-    fn tasks(&self) -> &Vec<String> {
+    fn marker_files(&self) -> Vec<&str> {
+        return JAKEFILE_NAMES.to_vec();
+    }
+
+    fn tasks(&self) -> &Vec<Task> {
         return &self.tasks;
     }
 
+    fn tasks_mut(&mut self) -> &mut Vec<Task> {
+        return &mut self.tasks;
+    }
+
     fn load(&mut self) -> Result<()> {
-        let maybe_module = parse_as_swc_module("jakefile.js")?;
+        let Some(path) = JAKEFILE_NAMES.iter().find(|name| Path::new(name).exists()) else {
+            return Ok(());
+        };
+
+        let maybe_module = parse_as_swc_module(path)?;
 
-        if let Some(module) = maybe_module {
-            self.tasks = get_task_fn_calls(&module);
+        if let Some((cm, module)) = maybe_module {
+            self.jakefile = Some(path);
+            self.tasks = get_task_fn_calls(&module, &cm);
+            for task in self.tasks.iter_mut() {
+                task.source = Some(path.into());
+            }
         };
 
         return Ok(());
     }
 
-    fn run(&self, task: &str, _args: &[String]) -> Result<i32> {
-        let mut jake = Command::new("./node_modules/.bin/jake");
-        eprintln!("[rt] using jake");
-        return self.execute(&mut jake.arg(task));
+    fn explain(&self, _task: &str) -> Vec<(String, String)> {
+        return vec![("jake".to_string(), npm::explain_bin("jake"))];
+    }
+
+    fn command(&self, task: &str, args: &[String]) -> Result<Command> {
+        let mut jake = npm::bin_command("jake");
+
+        // Jake finds the classic names by itself
+        if let Some(jakefile) = self
+            .jakefile
+            .filter(|name| !["Jakefile", "jakefile.js"].contains(name))
+        {
+            jake.arg("--jakefile").arg(jakefile);
+        }
+
+        jake.args(jake_args(task, args));
+        return Ok(jake);
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::rc::Rc;
+    use crate::swc::parse_code;
 
-    fn parse_tasks(code: &str) -> Vec<String> {
-        let cm: Lrc<SourceMap> = Default::default();
-        let fm = cm.new_source_file(
-            Rc::new(swc_common::FileName::Custom("test.js".into())),
-            code.into(),
-        );
-        let lexer = Lexer::new(
-            Syntax::Es(Default::default()),
-            Default::default(),
-            StringInput::from(&*fm),
-            None,
-        );
-        let mut parser = Parser::new_from(lexer);
-        let module = parser.parse_module().unwrap();
+    fn parse_task_objects(code: &str) -> Vec<Task> {
+        return parse_file_tasks("test.js", code);
+    }
 
-        return get_task_fn_calls(&module);
+    fn parse_file_tasks(path: &str, code: &str) -> Vec<Task> {
+        let (cm, module) = parse_code(path, code);
+        return get_task_fn_calls(&module, &cm);
+    }
+
+    fn parse_tasks(code: &str) -> Vec<String> {
+        return parse_task_objects(code)
+            .into_iter()
+            .map(|task| task.name)
+            .collect();
     }
 
     #[test]
@@ -211,4 +351,152 @@ mod tests {
 
         assert!(tasks.is_empty());
     }
+
+    #[test]
+    fn test_parse_namespaces() {
+        let code = r#"
+            namespace("db", () => {
+                task("migrate", () => {});
+                namespace("seed", function () {
+                    task("dev");
+                });
+            });
+            namespace("docs", () => task("build"));
+            task("test");
+        "#;
+        let tasks = parse_tasks(code);
+
+        assert_eq!(
+            tasks,
+            vec!["db:migrate", "db:seed:dev", "docs:build", "test"]
+        );
+    }
+
+    #[test]
+    fn test_parse_descriptions() {
+        let code = r#"
+            desc("Run the tests");
+            task("test", () => {});
+            task("lint");
+            jake.desc("Deploy it");
+            jake.task("deploy");
+        "#;
+        let tasks = parse_task_objects(code);
+
+        assert_eq!(tasks[0].description.as_deref(), Some("Run the tests"));
+        assert_eq!(tasks[1].description, None);
+        assert_eq!(tasks[2].name, "deploy");
+        assert_eq!(tasks[2].description.as_deref(), Some("Deploy it"));
+    }
+
+    #[test]
+    fn test_parse_file_tasks_and_deps() {
+        let code = r#"
+            task("default", ["build", "test"]);
+            file("dist/app.js", ["src/app.js"], () => {});
+            directory("dist");
+            rule(".o", ".c", () => {});
+            other.task("nope");
+        "#;
+        let tasks = parse_task_objects(code);
+        let names: Vec<&str> = tasks.iter().map(|task| task.name.as_str()).collect();
+
+        assert_eq!(names, vec!["default", "dist/app.js", "dist", ".o"]);
+        assert_eq!(tasks[0].deps, vec!["build", "test"]);
+        assert_eq!(tasks[1].deps, vec!["src/app.js"]);
+        assert!(tasks[2].deps.is_empty());
+        assert!(!tasks[2].hidden);
+        assert!(tasks[3].hidden);
+    }
+
+    #[test]
+    fn test_parse_typescript() {
+        let code = r#"
+            import { task, desc } from "jake";
+
+            interface Options { env: string }
+
+            desc("Deploy it");
+            task("deploy", async (env: string = "dev") => {});
+        "#;
+        let tasks = parse_file_tasks("jakefile.ts", code);
+
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].description.as_deref(), Some("Deploy it"));
+        assert_eq!(tasks[0].params[0].default.as_deref(), Some("dev"));
+    }
+
+    #[test]
+    fn test_parse_aliased_imports() {
+        let code = r#"
+            import { task as jakeTask, namespace as ns } from "jake";
+            import * as j from "jake";
+            import { task as other } from "other";
+
+            jakeTask("build");
+            ns("db", () => { j.task("migrate") });
+            other("nope");
+        "#;
+        assert_eq!(parse_tasks(code), vec!["build", "db:migrate"]);
+
+        let code = r#"
+            const { task: jakeTask, desc } = require("jake");
+            const jk = require("jake");
+
+            desc("Build it");
+            jakeTask("build");
+            jk.task("test");
+        "#;
+        let tasks = parse_task_objects(code);
+        let names: Vec<&str> = tasks.iter().map(|task| task.name.as_str()).collect();
+
+        assert_eq!(names, vec!["build", "test"]);
+        assert_eq!(tasks[0].description.as_deref(), Some("Build it"));
+    }
+
+    #[test]
+    fn test_jake_args() {
+        let args = |args: &[&str]| -> Vec<String> {
+            let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+            return jake_args("deploy", &args);
+        };
+
+        assert_eq!(args(&[]), vec!["deploy"]);
+        assert_eq!(args(&["prod", "eu"]), vec!["deploy[prod,eu]"]);
+        assert_eq!(
+            args(&["prod", "DRY_RUN=1", "--trace"]),
+            vec!["DRY_RUN=1", "--trace", "deploy[prod]"]
+        );
+        assert_eq!(args(&["a=b c"]), vec!["a=b c", "deploy"]);
+        assert_eq!(args(&["=x"]), vec!["deploy[=x]"]);
+    }
+
+    #[test]
+    fn test_parse_action_params() {
+        let code = r#"
+            task("deploy", ["build"], function (env = "staging", region) {});
+            task("copy", async (from, ...files) => {});
+            task("noop", aFunction);
+        "#;
+        let tasks = parse_task_objects(code);
+
+        assert_eq!(
+            tasks[0].params,
+            vec![
+                TaskParam {
+                    name: "env".to_string(),
+                    default: Some("staging".to_string()),
+                    variadic: false,
+                },
+                TaskParam {
+                    name: "region".to_string(),
+                    default: None,
+                    variadic: false,
+                },
+            ]
+        );
+        assert_eq!(tasks[1].params.len(), 2);
+        assert!(tasks[1].params[1].variadic);
+        assert!(tasks[2].params.is_empty());
+    }
 }
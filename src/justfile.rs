@@ -0,0 +1,582 @@
+use super::runner::{Runner, Task, TaskParam};
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+const JUSTFILE_NAMES: [&str; 3] = ["justfile", "Justfile", ".justfile"];
+
+#[derive(Debug, Clone, PartialEq)]
+struct JustRecipe {
+    name: String,
+    doc: Option<String>,
+    params: Vec<TaskParam>,
+    private: bool,
+    line: usize,
+    body: Vec<String>,
+}
+
+/// Split on whitespace but keep quoted strings and parenthesized expressions
+/// together. Eg. `a b='c d' e=(x + y)`
+fn split_words(input: &str) -> Vec<String> {
+    let mut words: Vec<String> = Vec::new();
+    let mut current = String::new();
+    let mut quote: Option<char> = None;
+    let mut depth = 0;
+
+    for a_char in input.chars() {
+        if let Some(q) = quote {
+            current.push(a_char);
+            if a_char == q {
+                quote = None;
+            }
+            continue;
+        }
+
+        match a_char {
+            '\'' | '"' | '`' => {
+                quote = Some(a_char);
+                current.push(a_char);
+            }
+            '(' => {
+                depth += 1;
+                current.push(a_char);
+            }
+            ')' => {
+                depth -= 1;
+                current.push(a_char);
+            }
+            c if c.is_whitespace() && depth == 0 => {
+                if !current.is_empty() {
+                    words.push(current.clone());
+                    current.clear();
+                }
+            }
+            _ => current.push(a_char),
+        }
+    }
+
+    if !current.is_empty() {
+        words.push(current);
+    }
+
+    return words;
+}
+
+/// Find the ':' ending a recipe header while skipping quoted default values
+fn find_header_colon(line: &str) -> Option<usize> {
+    let mut quote: Option<char> = None;
+    let mut depth = 0;
+
+    for (index, a_char) in line.char_indices() {
+        if let Some(q) = quote {
+            if a_char == q {
+                quote = None;
+            }
+            continue;
+        }
+
+        match a_char {
+            '\'' | '"' | '`' => quote = Some(a_char),
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            ':' if depth == 0 => return Some(index),
+            _ => {}
+        }
+    }
+
+    return None;
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+
+    let Some(first) = chars.next() else {
+        return false;
+    };
+
+    if !(first.is_ascii_alphabetic() || first == '_') {
+        return false;
+    }
+
+    return chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+}
+
+fn parse_param(word: &str) -> Option<TaskParam> {
+    let variadic = word.starts_with('+') || word.starts_with('*');
+    let word = word.trim_start_matches(['+', '*']).trim_start_matches('$');
+
+    let (name, default) = match word.split_once('=') {
+        Some((name, value)) => {
+            let value = value.trim();
+            // Only literal strings make sense as completion values
+            let default = if value.len() >= 2
+                && ((value.starts_with('\'') && value.ends_with('\''))
+                    || (value.starts_with('"') && value.ends_with('"')))
+            {
+                Some(value[1..value.len() - 1].to_string())
+            } else {
+                None
+            };
+            (name.trim(), default)
+        }
+        None => (word, None),
+    };
+
+    if !is_identifier(name) {
+        return None;
+    }
+
+    return Some(TaskParam {
+        name: name.to_string(),
+        default,
+        variadic,
+    });
+}
+
+/// Split an attribute list at the commas outside quotes and parentheses
+fn split_attributes(inner: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut quote: Option<char> = None;
+    let mut depth = 0;
+    let mut start = 0;
+
+    for (index, c) in inner.char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '\'' | '"') => quote = Some(c),
+            (None, '(') => depth += 1,
+            (None, ')') => depth -= 1,
+            (None, ',') if depth == 0 => {
+                parts.push(&inner[start..index]);
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+
+    parts.push(&inner[start..]);
+    return parts;
+}
+
+/// Parse the attribute names from a line like `[private]` or `[group('x'), no-cd]`
+fn parse_attributes(line: &str) -> Vec<(String, Option<String>)> {
+    let inner = line.trim().trim_start_matches('[').trim_end_matches(']');
+    let mut attributes = Vec::new();
+
+    for attribute in split_attributes(inner) {
+        let attribute = attribute.trim();
+        match attribute.split_once('(') {
+            Some((name, value)) => {
+                let value = value.trim_end().strip_suffix(')').unwrap_or(value).trim();
+                let value = value
+                    .strip_prefix(['\'', '"'])
+                    .and_then(|v| v.strip_suffix(['\'', '"']))
+                    .unwrap_or(value);
+                attributes.push((name.trim().to_string(), Some(value.to_string())));
+            }
+            None => attributes.push((attribute.to_string(), None)),
+        }
+    }
+
+    return attributes;
+}
+
+fn parse_justfile(content: &str) -> Vec<JustRecipe> {
+    let mut recipes: Vec<JustRecipe> = Vec::new();
+    let mut aliases: Vec<(String, String, bool, usize)> = Vec::new();
+
+    let mut comment: Option<String> = None;
+    let mut attributes: Vec<(String, Option<String>)> = Vec::new();
+    let mut in_recipe = false;
+
+    for (index, line) in content.lines().enumerate() {
+        // Recipe bodies are indented
+        if line.starts_with(' ') || line.starts_with('\t') {
+            if in_recipe {
+                if let Some(recipe) = recipes.last_mut() {
+                    recipe.body.push(line.trim().to_string());
+                }
+            }
+            comment = None;
+            attributes.clear();
+            continue;
+        }
+
+        let trimmed = line.trim_end();
+
+        if trimmed.is_empty() {
+            comment = None;
+            attributes.clear();
+            continue;
+        }
+
+        in_recipe = false;
+
+        if let Some(text) = trimmed.strip_prefix('#') {
+            // Shebangs are not doc comments
+            if !text.starts_with('!') {
+                comment = Some(text.trim().to_string());
+            }
+            continue;
+        }
+
+        if trimmed.starts_with('[') {
+            attributes.extend(parse_attributes(trimmed));
+            continue;
+        }
+
+        let private_attribute = attributes.iter().any(|(name, _)| name == "private");
+        let doc_attribute = attributes
+            .iter()
+            .find(|(name, _)| name == "doc")
+            .and_then(|(_, value)| value.clone());
+        let doc = doc_attribute.or(comment.take());
+        attributes.clear();
+
+        let words = split_words(trimmed);
+        let first_word = words.first().map(|w| w.as_str()).unwrap_or("");
+
+        if first_word == "alias" {
+            // alias b := build
+            if let (Some(name), Some(target)) = (words.get(1), words.get(3)) {
+                aliases.push((
+                    name.clone(),
+                    target.clone(),
+                    private_attribute || name.starts_with('_'),
+                    index + 1,
+                ));
+            }
+            continue;
+        }
+
+        if ["set", "export", "import", "import?", "mod", "mod?"].contains(&first_word) {
+            continue;
+        }
+
+        let Some(colon) = find_header_colon(trimmed) else {
+            continue;
+        };
+
+        // Variable assignment, eg. "version := '1.0'"
+        if trimmed[colon..].starts_with(":=") {
+            continue;
+        }
+
+        let header = split_words(&trimmed[..colon]);
+        let Some(name) = header.first() else {
+            continue;
+        };
+        let name = name.trim_start_matches('@');
+
+        if !is_identifier(name) {
+            continue;
+        }
+
+        recipes.push(JustRecipe {
+            name: name.to_string(),
+            doc,
+            params: header
+                .iter()
+                .skip(1)
+                .filter_map(|w| parse_param(w))
+                .collect(),
+            private: private_attribute || name.starts_with('_'),
+            line: index + 1,
+            body: Vec::new(),
+        });
+        in_recipe = true;
+    }
+
+    for (name, target, private, line) in aliases {
+        let Some(recipe) = recipes.iter().find(|r| r.name == target) else {
+            continue;
+        };
+
+        let alias = JustRecipe {
+            name,
+            doc: Some(format!("alias for {}", target)),
+            params: recipe.params.clone(),
+            private,
+            line,
+            body: recipe.body.clone(),
+        };
+        recipes.push(alias);
+    }
+
+    return recipes;
+}
+
+pub struct JustRunner {
+    tasks: Vec<Task>,
+}
+
+impl JustRunner {
+    pub fn new() -> Self {
+        return JustRunner { tasks: Vec::new() };
+    }
+
+    fn read_justfile() -> Result<(&'static str, Vec<JustRecipe>)> {
+        let Some(path) = JUSTFILE_NAMES.iter().find(|name| Path::new(name).exists()) else {
+            return Ok(("", Vec::new()));
+        };
+
+        let content =
+            fs::read_to_string(path).with_context(|| format!("Failed to read {}", path))?;
+
+        return Ok((path, parse_justfile(&content)));
+    }
+}
+
+impl Runner for JustRunner {
+    fn name(&self) -> &'static str {
+        return "justfile";
+    }
+
+    fn marker_files(&self) -> Vec<&str> {
+        return JUSTFILE_NAMES.to_vec();
+    }
+
+    fn tasks(&self) -> &Vec<Task> {
+        return &self.tasks;
+    }
+
+    fn tasks_mut(&mut self) -> &mut Vec<Task> {
+        return &mut self.tasks;
+    }
+
+    fn load(&mut self) -> Result<()> {
+        let (path, recipes) = JustRunner::read_justfile().context("Failed to read justfile")?;
+
+        self.tasks = recipes
+            .into_iter()
+            .map(|recipe| {
+                let mut task = Task::new(&recipe.name);
+                task.description = recipe.doc;
+                task.command = Some(recipe.body.join("\n")).filter(|c| !c.is_empty());
+                task.source = Some(path.into());
+                task.line = Some(recipe.line);
+                task.hidden = recipe.private;
+                task.params = recipe.params;
+                return task;
+            })
+            .collect();
+
+        return Ok(());
+    }
+
+    fn command(&self, task: &str, args: &[String]) -> Result<Command> {
+        let mut just = Command::new("just");
+        just.arg(task).args(args);
+        return Ok(just);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn recipe_names(content: &str) -> Vec<String> {
+        return parse_justfile(content)
+            .into_iter()
+            .map(|recipe| recipe.name)
+            .collect();
+    }
+
+    #[test]
+    fn test_parse_simple_recipes() {
+        let content = r#"
+build:
+    cargo build
+
+test: build
+    cargo test
+"#;
+
+        assert_eq!(recipe_names(content), vec!["build", "test"]);
+    }
+
+    #[test]
+    fn test_skips_settings_and_assignments() {
+        let content = r#"
+set shell := ["bash", "-c"]
+version := "1.0"
+export RUST_LOG := "debug"
+import 'other.just'
+
+@quiet:
+    echo hi
+"#;
+
+        assert_eq!(recipe_names(content), vec!["quiet"]);
+    }
+
+    #[test]
+    fn test_doc_comments() {
+        let content = r#"
+# Build the project
+build:
+    cargo build
+
+# Not attached
+
+test:
+    cargo test
+
+[doc('Deploy it')]
+deploy:
+    ./deploy.sh
+
+# Lint with attributes between
+[no-cd]
+lint:
+    cargo clippy
+"#;
+        let recipes = parse_justfile(content);
+
+        assert_eq!(recipes[0].doc.as_deref(), Some("Build the project"));
+        assert_eq!(recipes[1].doc, None);
+        assert_eq!(recipes[2].doc.as_deref(), Some("Deploy it"));
+        assert_eq!(
+            recipes[3].doc.as_deref(),
+            Some("Lint with attributes between")
+        );
+    }
+
+    #[test]
+    fn test_parse_attributes() {
+        let attribute = |name: &str, value: Option<&str>| {
+            return (name.to_string(), value.map(|v| v.to_string()));
+        };
+
+        assert_eq!(
+            parse_attributes("[group('x'), no-cd]"),
+            vec![attribute("group", Some("x")), attribute("no-cd", None)]
+        );
+        assert_eq!(
+            parse_attributes("[group('a, b')]"),
+            vec![attribute("group", Some("a, b"))]
+        );
+        assert_eq!(
+            parse_attributes(r#"[doc("x, y"), private]"#),
+            vec![attribute("doc", Some("x, y")), attribute("private", None)]
+        );
+        assert_eq!(
+            parse_attributes(r#"[confirm("Sure, really?")]"#),
+            vec![attribute("confirm", Some("Sure, really?"))]
+        );
+        assert_eq!(
+            parse_attributes("[doc('(a, b)')]"),
+            vec![attribute("doc", Some("(a, b)"))]
+        );
+    }
+
+    #[test]
+    fn test_private_recipes() {
+        let content = r#"
+[private]
+helper:
+    echo
+
+_hidden:
+    echo
+
+public:
+    echo
+"#;
+        let recipes = parse_justfile(content);
+
+        assert!(recipes[0].private);
+        assert!(recipes[1].private);
+        assert!(!recipes[2].private);
+    }
+
+    #[test]
+    fn test_parameters() {
+        let content = r#"
+deploy env='staging' region="eu north" +targets:
+    echo {{env}}
+
+serve $port=(default_port) *flags:
+    echo
+"#;
+        let recipes = parse_justfile(content);
+
+        assert_eq!(
+            recipes[0].params,
+            vec![
+                TaskParam {
+                    name: "env".to_string(),
+                    default: Some("staging".to_string()),
+                    variadic: false,
+                },
+                TaskParam {
+                    name: "region".to_string(),
+                    default: Some("eu north".to_string()),
+                    variadic: false,
+                },
+                TaskParam {
+                    name: "targets".to_string(),
+                    default: None,
+                    variadic: true,
+                },
+            ]
+        );
+
+        assert_eq!(recipes[1].params[0].name, "port");
+        assert_eq!(recipes[1].params[0].default, None);
+        assert!(recipes[1].params[1].variadic);
+    }
+
+    #[test]
+    fn test_aliases() {
+        let content = r#"
+alias b := build
+
+[private]
+alias t := test
+
+build target='debug':
+    cargo build
+test:
+    cargo test
+"#;
+        let recipes = parse_justfile(content);
+        let alias = recipes.iter().find(|r| r.name == "b").unwrap();
+
+        assert_eq!(alias.doc.as_deref(), Some("alias for build"));
+        assert_eq!(alias.params[0].name, "target");
+        assert!(recipes.iter().find(|r| r.name == "t").unwrap().private);
+    }
+
+    #[test]
+    fn test_lines_and_bodies() {
+        let content = "\n# Build\nbuild:\n    cargo build\n    strip app\n\ntest:\n";
+        let recipes = parse_justfile(content);
+
+        assert_eq!(recipes[0].line, 3);
+        assert_eq!(recipes[0].body, vec!["cargo build", "strip app"]);
+        assert_eq!(recipes[1].line, 7);
+        assert!(recipes[1].body.is_empty());
+    }
+
+    #[test]
+    fn test_arg_completions() {
+        let mut runner = JustRunner::new();
+        let recipe = parse_justfile("deploy env='staging' *rest:\n    echo\n").remove(0);
+        let mut task = Task::new(recipe.name);
+        task.params = recipe.params;
+        runner.tasks.push(task);
+
+        assert_eq!(
+            runner.arg_completions("deploy", &[]),
+            vec![("staging".to_string(), "env".to_string())]
+        );
+        assert!(runner.arg_completions("deploy", &["prod"]).is_empty());
+        assert!(runner
+            .arg_completions("deploy", &["prod", "a", "b"])
+            .is_empty());
+        assert!(runner.arg_completions("missing", &[]).is_empty());
+    }
+}
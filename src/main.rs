@@ -1,27 +1,58 @@
 use std::{
-    env,
-    io::{self},
+    env, fs,
+    io::{self, IsTerminal},
     path::{Path, PathBuf},
     process,
 };
 
+mod bash_autocomplete;
+mod cache;
+mod cargo;
 mod composer;
+mod deno;
 mod envfile;
+mod fish_autocomplete;
+mod grunt;
+mod gulp;
 mod jakefile;
+mod justfile;
+mod makefile;
 mod moon;
 mod npm;
+mod nu_autocomplete;
+mod parallel;
+mod picker;
+mod powershell_autocomplete;
+mod pyproject;
 mod runner;
 mod scripts;
+mod select;
+mod shell_init;
+mod shell_words;
+mod signals;
+mod swc;
+mod taskfile;
+#[cfg(test)]
+mod test_runner;
+mod watch;
 mod zsh_autocomplete;
 
 use anyhow::{bail, Context, Result};
+use cargo::CargoRunner;
 use composer::ComposerRunner;
+use deno::DenoRunner;
 use envfile::EnvFile;
+use grunt::GruntRunner;
+use gulp::GulpRunner;
 use jakefile::JakeRunner;
+use justfile::JustRunner;
+use makefile::MakefileRunner;
 use moon::MoonRunner;
 use npm::NpmRunner;
-use runner::Runner;
+use pyproject::PyprojectRunner;
+use runner::{Runner, Task};
 use scripts::ScriptsRunner;
+use taskfile::TaskfileRunner;
 
 use std::io::Write;
 
@@ -45,8 +76,10 @@ fn rt() -> Result<i32> {
         args.drain(1..3);
     }
 
+    let custom_runners_env = runners_env_name.clone();
     let runners_env_name = runners_env_name.unwrap_or_else(|| "RT_RUNNERS".to_string());
     let mut active_runners = env::var(&runners_env_name).unwrap_or_default();
+    let mut priority = env::var("RT_PRIORITY").unwrap_or_default();
 
     // Get project overrides from the .rtenv file in the current working directory
     let envfile = EnvFile::from_file(".rtenv");
@@ -54,6 +87,9 @@ fn rt() -> Result<i32> {
         if let Some(local) = envfile.get(&runners_env_name) {
             active_runners = local.to_string();
         }
+        if let Some(local) = envfile.get("RT_PRIORITY") {
+            priority = local.to_string();
+        }
     }
 
     let mut runners: Vec<Box<dyn Runner>> = Vec::new();
@@ -64,18 +100,38 @@ fn rt() -> Result<i32> {
             "" => {}
             "package.json" => runners.push(Box::new(NpmRunner::new())),
             "jakefile" => runners.push(Box::new(JakeRunner::new())),
+            "gulpfile" => runners.push(Box::new(GulpRunner::new())),
+            "Gruntfile" => runners.push(Box::new(GruntRunner::new())),
             "composer.json" => runners.push(Box::new(ComposerRunner::new())),
             "moon.yml" => runners.push(Box::new(MoonRunner::new())),
+            "Makefile" => runners.push(Box::new(MakefileRunner::new())),
+            "justfile" => runners.push(Box::new(JustRunner::new())),
+            "Taskfile.yml" => runners.push(Box::new(TaskfileRunner::new())),
+            "Cargo.toml" => runners.push(Box::new(CargoRunner::new())),
+            "deno.json" => runners.push(Box::new(DenoRunner::new())),
+            "pyproject.toml" => runners.push(Box::new(PyprojectRunner::new())),
             "scripts" => runners.push(Box::new(ScriptsRunner::new(runner_arg.to_string()))),
             _ => eprintln!("Unknown runner configured: '{}'", runner),
         }
     }
 
-    if runners.len() == 0 {
+    // The order of explicitly configured runners decides which one runs a
+    // task defined by several of them
+    let runners_configured = !runners.is_empty();
+
+    if runners.is_empty() {
         runners.push(Box::new(MoonRunner::new()));
         runners.push(Box::new(NpmRunner::new()));
+        runners.push(Box::new(DenoRunner::new()));
         runners.push(Box::new(JakeRunner::new()));
+        runners.push(Box::new(GulpRunner::new()));
+        runners.push(Box::new(GruntRunner::new()));
         runners.push(Box::new(ComposerRunner::new()));
+        runners.push(Box::new(MakefileRunner::new()));
+        runners.push(Box::new(JustRunner::new()));
+        runners.push(Box::new(TaskfileRunner::new()));
+        runners.push(Box::new(CargoRunner::new()));
+        runners.push(Box::new(PyprojectRunner::new()));
         runners.push(Box::new(ScriptsRunner::new("./scripts".to_string())));
         runners.push(Box::new(ScriptsRunner::new("./tools".to_string())));
         runners.push(Box::new(ScriptsRunner::new("./bin".to_string())));
@@ -95,20 +151,70 @@ fn rt() -> Result<i32> {
         return Ok(0);
     }
 
-    if arg == "--zsh-complete" {
-        // Try to find runner files in parent directories
-        let original_dir = env::current_dir().context("Failed to get current directory")?;
-        let found_dir = find_runner_files(&original_dir, &runners);
+    if arg == "--init" {
+        let Some(shell) = args.get(2) else {
+            bail!("Usage: rt --init <zsh|bash|fish|nu|powershell> [wrapper name]");
+        };
 
-        if let Some(dir) = found_dir {
-            env::set_current_dir(&dir).context("Failed to change directory")?;
-        }
+        let script = shell_init::get_init_script(
+            shell,
+            args.get(3).map(|name| name.as_str()),
+            custom_runners_env.as_deref(),
+        )?;
+        print!("{}", script);
 
-        for runner in runners.iter_mut() {
-            // Silence any loading errors intentionally. We do not want to see
-            // any errors when autocompleting
-            runner.load().ok();
-        }
+        return Ok(0);
+    }
+
+    if arg == "--bash-complete" {
+        load_for_completion(&mut runners)?;
+
+        // Bash exports these when the completer is registered with `complete -C`
+        let comp_line = env::var("COMP_LINE").unwrap_or_default();
+        let comp_point = env::var("COMP_POINT").ok().and_then(|p| p.parse().ok());
+
+        let mut out = io::stdout();
+        let completion =
+            bash_autocomplete::get_bash_autocompletion(&runners, &comp_line, comp_point);
+
+        out.write_all(completion.as_bytes()).ok();
+        out.flush().ok();
+
+        return Ok(0);
+    }
+
+    if arg == "--fish-complete" {
+        load_for_completion(&mut runners)?;
+
+        let buffer = args.get(2).unwrap_or(&default);
+        let mut out = io::stdout();
+        let completion = fish_autocomplete::get_fish_autocompletion(&runners, buffer);
+
+        out.write_all(completion.as_bytes()).ok();
+        out.flush().ok();
+
+        return Ok(0);
+    }
+
+    if arg == "--nu-complete" || arg == "--pwsh-complete" {
+        load_for_completion(&mut runners)?;
+
+        let buffer = args.get(2).unwrap_or(&default);
+        let completion = if arg == "--nu-complete" {
+            nu_autocomplete::get_nu_autocompletion(&runners, buffer)
+        } else {
+            powershell_autocomplete::get_powershell_autocompletion(&runners, buffer)
+        };
+
+        let mut out = io::stdout();
+        out.write_all(completion.as_bytes()).ok();
+        out.flush().ok();
+
+        return Ok(0);
+    }
+
+    if arg == "--zsh-complete" {
+        load_for_completion(&mut runners)?;
 
         if let Some(lbuffer) = args.get(2) {
             let mut out = io::stdout();
@@ -118,11 +224,11 @@ fn rt() -> Result<i32> {
                 args.get(3).unwrap_or(&"".to_string()),
             );
 
-            out.write(completion.as_bytes()).ok();
+            out.write_all(completion.as_bytes()).ok();
             out.flush().ok();
         } else {
             bail!(
-                "Using old .zshrc compdef definition. Please review your .zshrc and rt README.md"
+                "Using old .zshrc compdef definition. Replace it with: eval \"$(rt --init zsh)\""
             );
         }
 
@@ -133,83 +239,465 @@ fn rt() -> Result<i32> {
     let original_dir = env::current_dir().context("Failed to get current directory")?;
     let found_dir = find_runner_files(&original_dir, &runners);
 
-    if let Some(dir) = found_dir {
-        env::set_current_dir(&dir).context("Failed to change directory")?;
+    if let Some(dir) = &found_dir {
+        env::set_current_dir(dir).context("Failed to change directory")?;
+
+        // Remembered runner choices are saved to the project directory
+        if let Some(local) = EnvFile::from_file(".rtenv")
+            .ok()
+            .and_then(|envfile| envfile.get("RT_PRIORITY").map(|p| p.to_string()))
+        {
+            priority = local;
+        }
     }
 
     let mut errors: Vec<anyhow::Error> = Vec::new();
+    let mut failed_runners: Vec<String> = Vec::new();
 
     for runner in runners.iter_mut() {
         if let Err(e) = runner.load() {
+            failed_runners.push(runner.name().to_string());
             errors.push(e.context(format!("loading runner '{}'", runner.name())));
         }
     }
 
-    if arg == "" {
+    if arg == "--list" && args.get(2).is_some_and(|flag| flag == "--json") {
+        let listing = json_listing(found_dir.as_deref(), &runners, &failed_runners, &errors);
+        println!("{}", serde_json::to_string_pretty(&listing)?);
+
+        if !errors.is_empty() {
+            bail!("Some runners failed to load");
+        }
+    } else if arg == "--pick" {
+        for error in &errors {
+            eprintln!();
+            print_anyhow_error(error);
+            eprintln!();
+        }
+
+        return pick_task(&args[2..], &runners);
+    } else if arg.is_empty() || arg == "--list" {
         for error in &errors {
-            eprintln!("");
+            eprintln!();
             print_anyhow_error(error);
-            eprintln!("");
+            eprintln!();
         }
 
         for runner in runners {
-            let mut tasks = runner.tasks().clone();
-            if tasks.len() == 0 {
+            let tasks: Vec<&Task> = runner.tasks().iter().filter(|t| !t.hidden).collect();
+            if tasks.is_empty() {
                 continue;
             }
-            tasks.sort();
 
             println!("#{}:", runner.name());
-            for task in runner.tasks() {
-                println!("  {} ", task);
+            for task in tasks {
+                match &task.description {
+                    Some(description) => println!("  {} # {}", task.name, description),
+                    None => println!("  {} ", task.name),
+                }
             }
         }
 
-        if errors.len() > 0 {
+        if !errors.is_empty() {
             bail!("Some runners failed to load");
         }
     } else {
-        return run_task(&args[1..], &runners);
+        let order = if runners_configured {
+            RunnerOrder::Configured(&priority)
+        } else {
+            RunnerOrder::Priority(&priority)
+        };
+        if arg == "-p" || arg == "--parallel" {
+            return run_tasks(&args[2..], &runners, order, true);
+        }
+        if arg == "--dry-run" || arg == "--which" {
+            return dry_run(&args[2..], &runners, order);
+        }
+        if arg == "--exec" {
+            return exec_task(&args[2..], &runners, order);
+        }
+        if arg == "--watch" {
+            return watch_task(&args[2..], &runners, order);
+        }
+        return run_tasks(&args[1..], &runners, order, false);
     }
 
     return Ok(0);
 }
 
-fn run_task(args: &[String], runners: &Vec<Box<dyn Runner>>) -> Result<i32> {
-    let matching_runners: Vec<&Box<dyn Runner>> = runners
+/// Load runners from the project directory for shell completion
+fn load_for_completion(runners: &mut Vec<Box<dyn Runner>>) -> Result<()> {
+    // Try to find runner files in parent directories
+    let original_dir = env::current_dir().context("Failed to get current directory")?;
+    let found_dir = find_runner_files(&original_dir, runners);
+
+    if let Some(dir) = &found_dir {
+        env::set_current_dir(dir).context("Failed to change directory")?;
+    }
+
+    let project_dir = found_dir.unwrap_or(original_dir);
+    let cache_dir = cache::cache_dir();
+
+    for runner in runners.iter_mut() {
+        if let Some(cache_dir) = &cache_dir {
+            if cache::restore(cache_dir, &project_dir, runner.as_mut()) {
+                continue;
+            }
+        }
+
+        // Silence any loading errors intentionally. We do not want to see
+        // any errors when autocompleting
+        if runner.load().is_err() {
+            continue;
+        }
+
+        if let Some(cache_dir) = &cache_dir {
+            cache::store(cache_dir, &project_dir, runner.as_ref()).ok();
+        }
+    }
+
+    return Ok(());
+}
+
+fn json_listing(
+    project_dir: Option<&Path>,
+    runners: &[Box<dyn Runner>],
+    failed_runners: &[String],
+    errors: &[anyhow::Error],
+) -> serde_json::Value {
+    let runners: Vec<serde_json::Value> = runners
+        .iter()
+        .map(|runner| {
+            serde_json::json!({
+                "name": runner.name(),
+                "tasks": runner.tasks(),
+            })
+        })
+        .collect();
+
+    let errors: Vec<serde_json::Value> = failed_runners
+        .iter()
+        .zip(errors)
+        .map(|(runner, error)| {
+            // Skip the "loading runner" context as the runner has its own field
+            let message: Vec<String> = error.chain().skip(1).map(|e| e.to_string()).collect();
+            serde_json::json!({
+                "runner": runner,
+                "message": message.join(": "),
+            })
+        })
+        .collect();
+
+    return serde_json::json!({
+        "project_dir": project_dir,
+        "runners": runners,
+        "errors": errors,
+    });
+}
+
+/// Run a task chosen with the fuzzy picker. The arguments are passed to it.
+fn pick_task(args: &[String], runners: &[Box<dyn Runner>]) -> Result<i32> {
+    let tasks: Vec<(&dyn Runner, &Task)> = runners
         .iter()
-        .filter(|runner| runner.tasks().contains(&args[0]))
+        .flat_map(|runner| {
+            return runner
+                .tasks()
+                .iter()
+                .filter(|task| !task.hidden)
+                .map(move |task| (runner.as_ref(), task));
+        })
         .collect();
 
-    let selected_runner = if matching_runners.len() > 1 {
-        eprintln!("Multiple runners found for task: {}", args[0]);
+    if tasks.is_empty() {
+        bail!("No tasks found");
+    }
+
+    if !io::stdin().is_terminal() || !io::stderr().is_terminal() {
+        bail!("rt --pick requires a terminal");
+    }
+
+    let items: Vec<picker::PickItem> = tasks
+        .iter()
+        .map(|(runner, task)| picker::PickItem {
+            runner: runner.name(),
+            task,
+        })
+        .collect();
+
+    let Some(index) = picker::pick(&items)? else {
+        return Ok(130);
+    };
+
+    let (runner, task) = tasks[index];
+    eprintln!("[rt] {} from {}", task.name, runner.name());
+    return runner.run(&task.name, args);
+}
 
-        for (index, runner) in matching_runners.iter().enumerate() {
-            eprintln!("  {}: {}", index + 1, runner.name());
+/// How to pick the runner when several define the same task
+#[derive(Clone, Copy)]
+enum RunnerOrder<'a> {
+    /// Use RT_PRIORITY, then the first one in RT_RUNNERS
+    Configured(&'a str),
+    /// Use RT_PRIORITY or ask
+    Priority(&'a str),
+}
+
+/// Run one or more tasks like `rt build test -- --watch`. Tasks are run one
+/// after another until one fails or all at once in parallel mode.
+fn run_tasks(
+    args: &[String],
+    runners: &[Box<dyn Runner>],
+    order: RunnerOrder,
+    parallel: bool,
+) -> Result<i32> {
+    let (names, tasks, task_args) = resolve_tasks(args, runners, order)?;
+
+    if parallel && tasks.len() > 1 {
+        let mut jobs: Vec<parallel::Job> = Vec::new();
+        for (name, (runner, task)) in names.iter().zip(&tasks) {
+            jobs.push(parallel::Job {
+                label: name.clone(),
+                command: runner.command(task, &task_args)?,
+            });
         }
 
-        let choice = prompt_number(
-            &format!("Select runner (1-{}): ", matching_runners.len()),
-            matching_runners.len(),
-        )
-        .context("reading user input failed")?;
+        return Ok(parallel::run_parallel(jobs));
+    }
+
+    for (index, (runner, task)) in tasks.iter().enumerate() {
+        // A signal sent to rt stops the remaining tasks even when the task
+        // handled it. Ctrl-C only reaches the task when it has the terminal,
+        // then its exit code decides like for other failures.
+        if index > 0 {
+            if let Some(signal) = signals::received() {
+                eprintln!("[rt] Stopped by signal {}", signal);
+                return Ok(128 + signal);
+            }
+        }
+
+        let code = runner.run(task, &task_args)?;
+
+        if code != 0 {
+            if tasks.len() > 1 {
+                eprintln!("[rt] Task '{}' failed with exit code {}", task, code);
+            }
+            return Ok(code);
+        }
+    }
+
+    return Ok(0);
+}
+
+/// Task names as given, the resolved runners and tasks and the arguments
+/// passed to each of them
+type ResolvedTasks<'a> = (Vec<String>, Vec<(&'a dyn Runner, String)>, Vec<String>);
+
+fn resolve_tasks<'a>(
+    args: &[String],
+    runners: &'a [Box<dyn Runner>],
+    order: RunnerOrder,
+) -> Result<ResolvedTasks<'a>> {
+    let (names, task_args) = select::split_tasks(args, |name| {
+        return !select::find_candidates(runners, name).is_empty();
+    });
+
+    if names.is_empty() {
+        bail!("No task given");
+    }
+
+    let mut tasks: Vec<(&dyn Runner, String)> = Vec::new();
+    for name in &names {
+        tasks.push(resolve_task(name, runners, order)?);
+    }
+
+    return Ok((names, tasks, task_args));
+}
+
+/// Print what `rt <tasks>` would run without running anything
+fn dry_run(args: &[String], runners: &[Box<dyn Runner>], order: RunnerOrder) -> Result<i32> {
+    let (_, tasks, task_args) = resolve_tasks(args, runners, order)?;
+    let current_dir = env::current_dir().context("Failed to get current directory")?;
+
+    for (index, (runner, task)) in tasks.iter().enumerate() {
+        let command = runner.command(task, &task_args)?;
+
+        let directory = match command.get_current_dir() {
+            Some(dir) => current_dir.join(dir),
+            None => current_dir.clone(),
+        };
+
+        let argv: Vec<String> = std::iter::once(command.get_program())
+            .chain(command.get_args())
+            .map(|arg| shell_words::quote(&arg.to_string_lossy()))
+            .collect();
+
+        let mut details: Vec<(String, String)> = vec![
+            ("runner".to_string(), runner.name().to_string()),
+            ("task".to_string(), task.clone()),
+            ("directory".to_string(), directory.display().to_string()),
+        ];
+        details.extend(runner.explain(task));
+
+        for (key, value) in command.get_envs() {
+            let value = value.map(|v| v.to_string_lossy()).unwrap_or_default();
+            details.push((
+                "env".to_string(),
+                format!("{}={}", key.to_string_lossy(), shell_words::quote(&value)),
+            ));
+        }
+
+        details.push(("command".to_string(), argv.join(" ")));
+
+        if index > 0 {
+            println!();
+        }
+        for (key, value) in details {
+            println!("{:16} {}", format!("{}:", key), value);
+        }
+    }
+
+    return Ok(0);
+}
+
+/// Replace rt with the task process so signals and the exit status go
+/// directly to it, eg. for long running dev servers
+fn exec_task(args: &[String], runners: &[Box<dyn Runner>], order: RunnerOrder) -> Result<i32> {
+    let Some((name, task_args)) = args.split_first() else {
+        bail!("No task given");
+    };
+
+    let (runner, task) = resolve_task(name, runners, order)?;
+    let mut command = runner.command(&task, task_args)?;
 
-        matching_runners.get(choice - 1)
+    eprintln!("[rt] Using {}", command.get_program().to_string_lossy());
+    return Err(signals::exec(&mut command));
+}
+
+/// Rerun a task when files change with `rt --watch [glob] <task> [args]`.
+/// Without a glob the inputs declared for the task are watched.
+fn watch_task(args: &[String], runners: &[Box<dyn Runner>], order: RunnerOrder) -> Result<i32> {
+    let Some(first) = args.first() else {
+        bail!("Usage: rt --watch [glob] <task> [args...]");
+    };
+
+    let is_glob = args.len() > 1 && select::find_candidates(runners, first).is_empty();
+    let (glob, args) = if is_glob {
+        (Some(first.clone()), &args[1..])
     } else {
-        matching_runners.get(0)
+        (None, args)
+    };
+
+    let (runner, task) = resolve_task(&args[0], runners, order)?;
+
+    let patterns = match glob {
+        Some(glob) => vec![glob],
+        None => runner
+            .find_task(&task)
+            .map(|task| task.inputs.clone())
+            .unwrap_or_default(),
+    };
+
+    if patterns.is_empty() {
+        bail!(
+            "No files to watch for '{}'. Give a glob like: rt --watch 'src/**' {}",
+            task,
+            task
+        );
+    }
+
+    let outputs = runner
+        .find_task(&task)
+        .map(|task| task.outputs.clone())
+        .unwrap_or_default();
+
+    let mut command = runner.command(&task, &args[1..])?;
+    return watch::watch(&patterns, &outputs, &mut command);
+}
+
+/// Find the runner for a task name, possibly prefixed with the runner name
+fn resolve_task<'a>(
+    name: &str,
+    runners: &'a [Box<dyn Runner>],
+    order: RunnerOrder,
+) -> Result<(&'a dyn Runner, String)> {
+    let mut candidates = select::find_candidates(runners, name);
+
+    let is_decided = match order {
+        RunnerOrder::Configured(priority) => {
+            select::order_by_priority(&mut candidates, priority);
+            true
+        }
+        RunnerOrder::Priority(priority) => select::order_by_priority(&mut candidates, priority),
     };
 
-    if let Some(runner) = selected_runner {
-        return runner.run(&args[0], &args[1..]);
+    if candidates.len() > 1 && !is_decided {
+        let names: Vec<&str> = candidates.iter().map(|(runner, _)| runner.name()).collect();
+
+        if !io::stdin().is_terminal() {
+            bail!(
+                "Task '{}' is defined by multiple runners: {}. Pick one with a prefix like 'rt {}:{}' or set RT_PRIORITY",
+                name,
+                names.join(", "),
+                names[0],
+                name
+            );
+        }
+
+        eprintln!("Multiple runners found for task: {}", name);
+
+        for (index, name) in names.iter().enumerate() {
+            eprintln!("  {}: {}", index + 1, name);
+        }
+
+        let (choice, remember) = prompt_number(
+            &format!(
+                "Select runner (1-{}, add ! to remember): ",
+                candidates.len()
+            ),
+            candidates.len(),
+        )
+        .context("reading user input failed")?;
+
+        let selected = candidates.swap_remove(choice - 1);
+        if remember {
+            remember_runner(selected.0.name())?;
+        }
+
+        return Ok(selected);
+    }
+
+    if !candidates.is_empty() {
+        return Ok(candidates.swap_remove(0));
     }
 
-    bail!("Unknown task '{}'", args[0]);
+    bail!("Unknown task '{}'", name);
 }
 
-fn prompt_number(prompt: &str, max: usize) -> Result<usize> {
+/// Put the runner first in RT_PRIORITY of the project .rtenv file
+fn remember_runner(runner: &str) -> Result<()> {
+    let contents = match fs::read_to_string(".rtenv") {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e).context("Failed to read .rtenv"),
+    };
+
+    let current = EnvFile::new(&contents);
+    let priority = select::prepend_priority(current.get("RT_PRIORITY"), runner);
+
+    fs::write(
+        ".rtenv",
+        envfile::set_var(&contents, "RT_PRIORITY", &priority),
+    )
+    .context("Failed to write .rtenv")?;
+    eprintln!("[rt] Saved RT_PRIORITY={} to .rtenv", priority);
+
+    return Ok(());
+}
+
+fn prompt_number(prompt: &str, max: usize) -> Result<(usize, bool)> {
     let mut out = io::stdout();
     loop {
-        out.write(prompt.as_bytes())?;
+        out.write_all(prompt.as_bytes())?;
         out.flush()?;
 
         let mut input = String::new();
@@ -217,14 +705,10 @@ fn prompt_number(prompt: &str, max: usize) -> Result<usize> {
             .read_line(&mut input)
             .context("failed to read stdin")?;
 
-        let choice = input.trim().chars().next().unwrap_or('1');
-        if let Some(digit) = choice.to_digit(10) {
-            let digit = digit as usize;
-            if digit <= max {
-                return Ok(digit);
-            }
+        if let Some(choice) = select::parse_choice(&input, max) {
+            return Ok(choice);
         }
-        eprintln!("Invalid choice: {}", choice);
+        eprintln!("Invalid choice: {}", input.trim());
     }
 }
 
@@ -266,24 +750,10 @@ fn find_runner_files(start_dir: &Path, runners: &Vec<Box<dyn Runner>>) -> Option
 
 fn has_runner_files(dir: &Path, runners: &Vec<Box<dyn Runner>>) -> bool {
     for runner in runners {
-        let runner_name = runner.name();
-
-        // Extract the file name from runner name
-        let file_to_check = if runner_name.starts_with("scripts:") {
-            // For scripts runners, extract the directory path
-            let dir_path = runner_name.strip_prefix("scripts:").unwrap_or("");
-            if !dir_path.is_empty() {
-                dir.join(dir_path).exists()
-            } else {
-                false
+        for file in runner.marker_files() {
+            if !file.is_empty() && dir.join(file).exists() {
+                return true;
             }
-        } else {
-            // For file-based runners (package.json, moon.yml, composer.json, jakefile)
-            dir.join(runner_name).exists()
-        };
-
-        if file_to_check {
-            return true;
         }
     }
     false
@@ -0,0 +1,482 @@
+use super::runner::{Runner, Task};
+use anyhow::{Context, Result};
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+// Same lookup order as GNU make
+const MAKEFILE_NAMES: [&str; 3] = ["GNUmakefile", "makefile", "Makefile"];
+
+const DIRECTIVES: [&str; 11] = [
+    "ifeq", "ifneq", "ifdef", "ifndef", "else", "endif", "export", "unexport", "override", "vpath",
+    "undefine",
+];
+
+#[derive(Debug, Default, PartialEq)]
+struct ParsedMakefile {
+    targets: Vec<Task>,
+    phony: Vec<String>,
+    includes: Vec<String>,
+}
+
+struct LogicalLine {
+    number: usize,
+    code: String,
+    comment: Option<String>,
+}
+
+/// Join backslash continued lines and split off comments
+fn logical_lines(content: &str) -> Vec<LogicalLine> {
+    let mut lines: Vec<LogicalLine> = Vec::new();
+    let mut current = String::new();
+    let mut start = 1;
+
+    for (index, line) in content.lines().enumerate() {
+        if current.is_empty() {
+            start = index + 1;
+        }
+
+        if let Some(stripped) = line.strip_suffix('\\') {
+            current.push_str(stripped);
+            current.push(' ');
+            continue;
+        }
+
+        current.push_str(line);
+        lines.push(split_comment(start, &current));
+        current.clear();
+    }
+
+    if !current.is_empty() {
+        lines.push(split_comment(start, &current));
+    }
+
+    return lines;
+}
+
+fn split_comment(number: usize, line: &str) -> LogicalLine {
+    let mut code = String::new();
+    let mut escaped = false;
+
+    for (index, a_char) in line.char_indices() {
+        if a_char == '#' && !escaped {
+            let comment = line[index..].trim_start_matches('#').trim();
+            return LogicalLine {
+                number,
+                code,
+                comment: Some(comment.to_string()).filter(|c| !c.is_empty()),
+            };
+        }
+        escaped = a_char == '\\';
+        code.push(a_char);
+    }
+
+    return LogicalLine {
+        number,
+        code,
+        comment: None,
+    };
+}
+
+/// Find the first top level ':' or '=' ignoring anything inside $(...) or ${...}
+fn find_separator(line: &str) -> Option<(usize, char)> {
+    let mut depth = 0;
+    let mut prev = ' ';
+
+    for (index, a_char) in line.char_indices() {
+        match a_char {
+            '(' | '{' if prev == '$' || depth > 0 => depth += 1,
+            ')' | '}' if depth > 0 => depth -= 1,
+            ':' | '=' if depth == 0 => return Some((index, a_char)),
+            _ => {}
+        }
+        prev = a_char;
+    }
+
+    return None;
+}
+
+fn parse_makefile(content: &str) -> ParsedMakefile {
+    let mut parsed = ParsedMakefile::default();
+    let mut in_define = false;
+    // Targets of the rule whose recipe is currently being read
+    let mut rule_targets: Vec<usize> = Vec::new();
+
+    for logical_line in logical_lines(content) {
+        let line = logical_line.code;
+        let trimmed = line.trim();
+        let first_word = trimmed.split_whitespace().next().unwrap_or("");
+
+        if in_define {
+            if first_word == "endef" {
+                in_define = false;
+            }
+            continue;
+        }
+
+        // Recipe lines
+        if line.starts_with('\t') {
+            if trimmed.is_empty() {
+                continue;
+            }
+
+            for index in &rule_targets {
+                let task = &mut parsed.targets[*index];
+                task.command = Some(match task.command.take() {
+                    Some(command) => format!("{}\n{}", command, trimmed),
+                    None => trimmed.to_string(),
+                });
+            }
+            continue;
+        }
+
+        if !trimmed.is_empty() {
+            rule_targets.clear();
+        }
+
+        if first_word == "define" {
+            in_define = true;
+            continue;
+        }
+
+        if first_word == "include" || first_word == "-include" || first_word == "sinclude" {
+            for file in trimmed.split_whitespace().skip(1) {
+                parsed.includes.push(file.to_string());
+            }
+            continue;
+        }
+
+        if DIRECTIVES.contains(&first_word) {
+            continue;
+        }
+
+        let Some((index, separator)) = find_separator(trimmed) else {
+            continue;
+        };
+
+        // FOO = bar, FOO ?= bar, FOO += bar etc.
+        if separator == '=' {
+            continue;
+        }
+
+        let rest = trimmed[index..].trim_start_matches(':');
+
+        // FOO := bar, FOO ::= bar
+        if rest.starts_with('=') {
+            continue;
+        }
+
+        // Target specific variable, eg. "build: CFLAGS = -O2"
+        if let Some((_, '=')) = find_separator(rest) {
+            continue;
+        }
+
+        for target in trimmed[..index].split_whitespace() {
+            if target == ".PHONY" {
+                for name in rest.split_whitespace() {
+                    if name != "|" {
+                        parsed.phony.push(name.to_string());
+                    }
+                }
+                continue;
+            }
+
+            // Special targets, suffix rules, pattern rules and computed names
+            if target.starts_with('.') || target.contains('%') || target.contains('$') {
+                continue;
+            }
+
+            // Only the first rule defines the location and recipe
+            if parsed.targets.iter().any(|t| t.name == target) {
+                continue;
+            }
+
+            let mut task = Task::new(target);
+            task.line = Some(logical_line.number);
+            // Self documenting Makefiles use "target: ## Description"
+            task.description = logical_line.comment.clone();
+            rule_targets.push(parsed.targets.len());
+            parsed.targets.push(task);
+        }
+    }
+
+    return parsed;
+}
+
+/// Phony targets are always tasks. Without a .PHONY declaration targets that
+/// look like file paths are assumed to be build outputs.
+fn is_task_like(target: &str, phony: &HashSet<String>) -> bool {
+    if phony.contains(target) {
+        return true;
+    }
+
+    return !target.contains('/') && !target.contains('.');
+}
+
+pub struct MakefileRunner {
+    tasks: Vec<Task>,
+    /// Included files, also the ones that do not exist yet
+    includes: Vec<String>,
+}
+
+impl MakefileRunner {
+    pub fn new() -> Self {
+        return MakefileRunner {
+            tasks: Vec::new(),
+            includes: Vec::new(),
+        };
+    }
+
+    fn read_file(
+        path: &str,
+        parsed: &mut ParsedMakefile,
+        visited: &mut HashSet<String>,
+    ) -> Result<()> {
+        if !visited.insert(path.to_string()) {
+            return Ok(());
+        }
+
+        let content =
+            fs::read_to_string(path).with_context(|| format!("Failed to read {}", path))?;
+        let file = parse_makefile(&content);
+
+        for mut task in file.targets {
+            if parsed.targets.iter().any(|t| t.name == task.name) {
+                continue;
+            }
+            task.source = Some(path.into());
+            parsed.targets.push(task);
+        }
+        parsed.phony.extend(file.phony);
+
+        for include in file.includes {
+            if include.contains('$') || include.contains('*') {
+                continue;
+            }
+
+            // Includes are relative to the directory make is invoked in
+            if Path::new(&include).exists() {
+                MakefileRunner::read_file(&include, parsed, visited)?;
+            } else {
+                visited.insert(include);
+            }
+        }
+
+        return Ok(());
+    }
+
+    /// The targets and the included files
+    fn read_makefile() -> Result<(Vec<Task>, Vec<String>)> {
+        let Some(path) = MAKEFILE_NAMES.iter().find(|name| Path::new(name).exists()) else {
+            return Ok((Vec::new(), Vec::new()));
+        };
+
+        let mut parsed = ParsedMakefile::default();
+        let mut visited = HashSet::new();
+
+        MakefileRunner::read_file(path, &mut parsed, &mut visited)?;
+
+        let phony: HashSet<String> = parsed.phony.into_iter().collect();
+
+        for task in parsed.targets.iter_mut() {
+            task.hidden = !is_task_like(&task.name, &phony);
+        }
+
+        visited.remove(*path);
+        let mut includes: Vec<String> = visited.into_iter().collect();
+        includes.sort();
+
+        return Ok((parsed.targets, includes));
+    }
+}
+
+impl Runner for MakefileRunner {
+    fn name(&self) -> &'static str {
+        return "Makefile";
+    }
+
+    fn marker_files(&self) -> Vec<&str> {
+        return MAKEFILE_NAMES.to_vec();
+    }
+
+    fn cache_files(&self) -> Vec<&str> {
+        return self.includes.iter().map(String::as_str).collect();
+    }
+
+    fn tasks(&self) -> &Vec<Task> {
+        return &self.tasks;
+    }
+
+    fn tasks_mut(&mut self) -> &mut Vec<Task> {
+        return &mut self.tasks;
+    }
+
+    fn load(&mut self) -> Result<()> {
+        (self.tasks, self.includes) =
+            MakefileRunner::read_makefile().context("Failed to read Makefile")?;
+        return Ok(());
+    }
+
+    fn command(&self, task: &str, args: &[String]) -> Result<Command> {
+        let mut make = Command::new("make");
+        make.arg(task).args(args);
+        return Ok(make);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_tasks(content: &str) -> Vec<String> {
+        let parsed = parse_makefile(content);
+        let phony: HashSet<String> = parsed.phony.into_iter().collect();
+
+        return parsed
+            .targets
+            .into_iter()
+            .map(|target| target.name)
+            .filter(|target| is_task_like(target, &phony))
+            .collect();
+    }
+
+    #[test]
+    fn test_parse_simple_targets() {
+        let content = "build:\n\tcargo build\n\ntest: build\n\tcargo test\n";
+
+        assert_eq!(parse_tasks(content), vec!["build", "test"]);
+    }
+
+    #[test]
+    fn test_skips_variable_assignments() {
+        let content = r#"
+CC = gcc
+CFLAGS := -O2
+PREFIX ?= /usr/local
+LDFLAGS += -lm
+SHELL_OUT != echo hi
+IMMEDIATE ::= value
+URL = http://example.com
+
+all:
+	echo $(CC)
+"#;
+
+        assert_eq!(parse_tasks(content), vec!["all"]);
+    }
+
+    #[test]
+    fn test_skips_pattern_and_special_targets() {
+        let content = r#"
+.PHONY: clean
+.DEFAULT_GOAL := clean
+%.o: %.c
+	$(CC) -c $<
+.c.o:
+	$(CC) -c $<
+$(BUILD_DIR)/app: main.o
+	$(CC) -o $@ $^
+clean:
+	rm -f *.o
+"#;
+
+        assert_eq!(parse_tasks(content), vec!["clean"]);
+    }
+
+    #[test]
+    fn test_file_targets_are_not_tasks_unless_phony() {
+        let content = r#"
+.PHONY: docs/index.html
+main.o: main.c
+build/app: main.o
+docs/index.html:
+dist:
+"#;
+
+        assert_eq!(parse_tasks(content), vec!["docs/index.html", "dist"]);
+    }
+
+    #[test]
+    fn test_multiple_targets_and_double_colon() {
+        let content = "lint fmt: deps\n\techo\ncheck:: lint\n";
+
+        assert_eq!(parse_tasks(content), vec!["lint", "fmt", "check"]);
+    }
+
+    #[test]
+    fn test_skips_target_specific_variables() {
+        let content = "debug: CFLAGS = -g\ndebug: build\n\techo\n";
+
+        assert_eq!(parse_tasks(content), vec!["debug"]);
+    }
+
+    #[test]
+    fn test_skips_recipes_comments_and_defines() {
+        let content = r#"
+# comment: not a target
+define TEMPLATE
+inner: target
+endef
+run: # trailing comment
+	@echo "not: a target"
+"#;
+
+        assert_eq!(parse_tasks(content), vec!["run"]);
+    }
+
+    #[test]
+    fn test_line_continuations() {
+        let content = ".PHONY: a \\\n  b\na \\\n b:\n";
+        let parsed = parse_makefile(content);
+
+        assert_eq!(parsed.phony, vec!["a", "b"]);
+        assert_eq!(parse_tasks(content), vec!["a", "b"]);
+        assert_eq!(parsed.targets[0].line, Some(3));
+    }
+
+    #[test]
+    fn test_descriptions_and_recipes() {
+        let content = r#"
+build: deps ## Build the binary
+	cargo build
+	strip target/app
+
+test: # Run tests
+	cargo test
+clean:
+"#;
+        let parsed = parse_makefile(content);
+
+        assert_eq!(parsed.targets[0].name, "build");
+        assert_eq!(parsed.targets[0].line, Some(2));
+        assert_eq!(
+            parsed.targets[0].description.as_deref(),
+            Some("Build the binary")
+        );
+        assert_eq!(
+            parsed.targets[0].command.as_deref(),
+            Some("cargo build\nstrip target/app")
+        );
+        assert_eq!(parsed.targets[1].description.as_deref(), Some("Run tests"));
+        assert_eq!(parsed.targets[1].command.as_deref(), Some("cargo test"));
+        assert_eq!(parsed.targets[2].command, None);
+    }
+
+    #[test]
+    fn test_collects_includes() {
+        let content = "include common.mk other.mk\n-include deps.d\nsinclude local.mk\n";
+        let parsed = parse_makefile(content);
+
+        assert_eq!(
+            parsed.includes,
+            vec!["common.mk", "other.mk", "deps.d", "local.mk"]
+        );
+    }
+
+    #[test]
+    fn test_runner_name() {
+        let runner = MakefileRunner::new();
+        assert_eq!(runner.name(), "Makefile");
+    }
+}
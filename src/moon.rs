@@ -1,7 +1,9 @@
-use super::runner::Runner;
+use super::runner::{find_key_line, Runner, Task};
 use anyhow::{bail, Context, Result};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::env;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 use std::{fs, io::ErrorKind};
 
@@ -9,6 +11,8 @@ use std::{fs, io::ErrorKind};
 struct MoonConfig {
     #[serde(default)]
     tasks: HashMap<String, MoonTask>,
+    #[serde(default, rename = "fileGroups")]
+    file_groups: HashMap<String, Vec<String>>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -17,10 +21,67 @@ struct MoonTask {
     command: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    inputs: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    outputs: Option<Vec<String>>,
+}
+
+/// The closest parent directory with the `.moon` workspace config
+fn find_workspace_root() -> Option<PathBuf> {
+    let current = env::current_dir().ok()?;
+    return current
+        .ancestors()
+        .find(|dir| dir.join(".moon").is_dir())
+        .map(|dir| dir.to_path_buf());
+}
+
+/// Resolve task inputs to globs. File group tokens like `@group(sources)`
+/// are expanded and workspace relative `/paths` are made absolute.
+/// Environment variable inputs are skipped. Tasks without inputs depend on
+/// every file in the project.
+fn expand_inputs(
+    inputs: Option<&Vec<String>>,
+    file_groups: &HashMap<String, Vec<String>>,
+    workspace_root: Option<&Path>,
+) -> Vec<String> {
+    let Some(inputs) = inputs else {
+        return vec!["**/*".to_string()];
+    };
+
+    let mut globs: Vec<String> = Vec::new();
+
+    for input in inputs {
+        if input.starts_with('$') {
+            continue;
+        }
+
+        let group = ["@group(", "@globs(", "@files(", "@dirs(", "@root("]
+            .iter()
+            .find_map(|token| input.strip_prefix(token))
+            .and_then(|rest| rest.strip_suffix(')'));
+
+        let paths = match group {
+            Some(name) => file_groups.get(name).cloned().unwrap_or_default(),
+            None if input.starts_with('@') => continue,
+            None => vec![input.clone()],
+        };
+
+        for path in paths {
+            let glob = match (path.strip_prefix('/'), workspace_root) {
+                (Some(relative), Some(root)) => root.join(relative).to_string_lossy().to_string(),
+                (Some(relative), None) => relative.to_string(),
+                (None, _) => path,
+            };
+            globs.push(glob);
+        }
+    }
+
+    return globs;
 }
 
 pub struct MoonRunner {
-    tasks: Vec<String>,
+    tasks: Vec<Task>,
 }
 
 impl MoonRunner {
@@ -28,9 +89,9 @@ impl MoonRunner {
         return MoonRunner { tasks: Vec::new() };
     }
 
-    fn read_moon_yml() -> Result<Vec<String>> {
+    fn read_moon_yml() -> Result<Vec<Task>> {
         let content = fs::read_to_string("moon.yml");
-        let mut task_names: Vec<String> = Vec::new();
+        let mut task_names: Vec<Task> = Vec::new();
 
         let content = match content {
             Ok(content) => content,
@@ -46,10 +107,31 @@ impl MoonRunner {
         let config: MoonConfig =
             serde_yaml::from_str(&content).context("Failed to parse moon.yml")?;
 
-        for (key, _value) in config.tasks.iter() {
-            task_names.push(key.to_string());
+        let workspace_root = find_workspace_root();
+
+        for (key, value) in config.tasks.iter() {
+            let mut task = Task::new(key);
+            task.inputs = expand_inputs(
+                value.inputs.as_ref(),
+                &config.file_groups,
+                workspace_root.as_deref(),
+            );
+            if let Some(outputs) = &value.outputs {
+                task.outputs = expand_inputs(
+                    Some(outputs),
+                    &config.file_groups,
+                    workspace_root.as_deref(),
+                );
+            }
+            task.description = value.description.clone();
+            task.command = value.command.clone();
+            task.source = Some("moon.yml".into());
+            task.line = find_key_line(&content, &["tasks", key]);
+            task_names.push(task);
         }
 
+        task_names.sort_by(|a, b| a.name.cmp(&b.name));
+
         return Ok(task_names);
     }
 }
@@ -59,20 +141,24 @@ impl Runner for MoonRunner {
         return "moon.yml";
     }
 
-    fn tasks(&self) -> &Vec<String> {
+    fn tasks(&self) -> &Vec<Task> {
         return &self.tasks;
     }
 
+    fn tasks_mut(&mut self) -> &mut Vec<Task> {
+        return &mut self.tasks;
+    }
+
     fn load(&mut self) -> Result<()> {
         let tasks = MoonRunner::read_moon_yml().context("Failed to read moon.yml")?;
         self.tasks = tasks;
         return Ok(());
     }
 
-    fn run(&self, task: &str, args: &[String]) -> Result<i32> {
-        eprintln!("[rt] Using moon");
+    fn command(&self, task: &str, args: &[String]) -> Result<Command> {
         let mut moon = Command::new("moon");
-        return self.execute(moon.arg("run").arg(task).args(args));
+        moon.arg("run").arg(task).args(args);
+        return Ok(moon);
     }
 }
 
@@ -242,4 +328,36 @@ fileGroups:
         let runner = MoonRunner::new();
         assert_eq!(runner.tasks().len(), 0);
     }
+
+    #[test]
+    fn test_expand_inputs() {
+        let yaml = r#"
+fileGroups:
+  sources:
+    - "src/**/*"
+    - "/shared/types"
+tasks:
+  build:
+    inputs:
+      - "@group(sources)"
+      - "package.json"
+      - "$NODE_ENV"
+  lint:
+    command: eslint
+"#;
+        let config: MoonConfig = serde_yaml::from_str(yaml).unwrap();
+        let inputs = |name: &str| {
+            return expand_inputs(
+                config.tasks[name].inputs.as_ref(),
+                &config.file_groups,
+                Some(Path::new("/repo")),
+            );
+        };
+
+        assert_eq!(
+            inputs("build"),
+            vec!["src/**/*", "/repo/shared/types", "package.json"]
+        );
+        assert_eq!(inputs("lint"), vec!["**/*"]);
+    }
 }
@@ -1,12 +1,179 @@
-use super::runner::Runner;
+use super::runner::{find_key_line, Runner, Task, TaskFlag};
 use anyhow::{bail, Context, Result};
 use serde_json::Value;
-use std::path::Path;
+use std::env;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 use std::{fs, io::ErrorKind};
 
+type CliFlags = &'static [(&'static str, &'static str)];
+
+/// Common flags of CLIs often wrapped by package.json scripts
+const KNOWN_CLIS: [(&str, CliFlags); 9] = [
+    (
+        "jest",
+        &[
+            ("--watch", "Watch files for changes"),
+            ("--coverage", "Collect coverage"),
+            ("--updateSnapshot", "Re-record failing snapshots"),
+            ("--runInBand", "Run tests serially"),
+            ("--testNamePattern", "Run tests matching a pattern"),
+        ],
+    ),
+    (
+        "vitest",
+        &[
+            ("--watch", "Watch files for changes"),
+            ("--run", "Run once without watching"),
+            ("--coverage", "Collect coverage"),
+            ("--update", "Update snapshots"),
+            ("--reporter", "Reporter to use"),
+        ],
+    ),
+    (
+        "mocha",
+        &[
+            ("--watch", "Watch files for changes"),
+            ("--grep", "Run tests matching a pattern"),
+            ("--bail", "Stop after the first failure"),
+        ],
+    ),
+    (
+        "playwright",
+        &[
+            ("--headed", "Run in headed browsers"),
+            ("--debug", "Run with the inspector"),
+            ("--ui", "Run in UI mode"),
+            ("--project", "Run only the given project"),
+            ("--grep", "Run tests matching a pattern"),
+        ],
+    ),
+    (
+        "eslint",
+        &[
+            ("--fix", "Fix problems automatically"),
+            ("--cache", "Only check changed files"),
+            ("--quiet", "Report errors only"),
+            ("--max-warnings", "Warning count to fail on"),
+        ],
+    ),
+    (
+        "prettier",
+        &[
+            ("--write", "Format files in place"),
+            ("--check", "Check if files are formatted"),
+            ("--list-different", "Print unformatted files"),
+        ],
+    ),
+    (
+        "tsc",
+        &[
+            ("--watch", "Watch files for changes"),
+            ("--noEmit", "Only type check"),
+            ("--project", "Path to tsconfig.json"),
+            ("--build", "Build project references"),
+        ],
+    ),
+    (
+        "vite",
+        &[
+            ("--host", "Listen on all addresses"),
+            ("--port", "Port to listen on"),
+            ("--open", "Open the browser"),
+            ("--mode", "Env mode"),
+        ],
+    ),
+    (
+        "webpack",
+        &[
+            ("--watch", "Watch files for changes"),
+            ("--mode", "development or production"),
+            ("--config", "Path to the config file"),
+        ],
+    ),
+];
+
+/// Flags of the CLI receiving the extra arguments of a script. They are
+/// appended to the script so it is the last command in a chain.
+fn wrapped_cli_flags(script: &str) -> Vec<TaskFlag> {
+    let last_command = script.rsplit(['&', ';', '|']).next().unwrap_or(script);
+
+    let program = last_command
+        .split_whitespace()
+        .find(|word| !word.contains('=') && *word != "cross-env" && *word != "npx");
+
+    let Some(program) = program else {
+        return Vec::new();
+    };
+
+    // ./node_modules/.bin/jest
+    let program = program.rsplit('/').next().unwrap_or(program);
+
+    let Some((_, flags)) = KNOWN_CLIS.iter().find(|(name, _)| *name == program) else {
+        return Vec::new();
+    };
+
+    return flags
+        .iter()
+        .map(|(flag, description)| TaskFlag::new(*flag, Some(description)))
+        .collect();
+}
+
+/// Lock files in the order they are checked and the package manager they
+/// select. The ../../ is for packges/* style monorepo
+const LOCK_FILES: [(&str, &str); 8] = [
+    ("pnpm-lock.yaml", "pnpm"),
+    ("../../pnpm-lock.yaml", "pnpm"),
+    ("bun.lockb", "bun"),
+    ("bun.lock", "bun"),
+    ("../../bun.lockb", "bun"),
+    ("../../bun.lock", "bun"),
+    ("yarn.lock", "yarn"),
+    ("../../yarn.lock", "yarn"),
+];
+
+/// The package manager and the lock file it was detected from
+fn detect_package_manager() -> (&'static str, Option<&'static str>) {
+    for (lock_file, manager) in LOCK_FILES {
+        if Path::new(lock_file).exists() {
+            return (manager, Some(lock_file));
+        }
+    }
+
+    return ("npm", None);
+}
+
+/// Closest `node_modules/.bin/<name>` from the directory or its parents
+fn find_bin(dir: &Path, name: &str) -> Option<PathBuf> {
+    return dir
+        .ancestors()
+        .map(|dir| dir.join("node_modules/.bin").join(name))
+        .find(|path| path.is_file());
+}
+
+/// Command running an installed node CLI or `npx <name>` when it is not
+/// installed
+pub fn bin_command(name: &str) -> Command {
+    return match env::current_dir().ok().and_then(|dir| find_bin(&dir, name)) {
+        Some(path) => Command::new(path),
+        None => {
+            let mut npx = Command::new("npx");
+            npx.arg(name);
+            npx
+        }
+    };
+}
+
+/// Where `bin_command` finds the CLI for `rt --dry-run`
+pub fn explain_bin(name: &str) -> String {
+    return match env::current_dir().ok().and_then(|dir| find_bin(&dir, name)) {
+        Some(path) => path.display().to_string(),
+        None => format!("npx {} (no node_modules/.bin/{} found)", name, name),
+    };
+}
+
 pub struct NpmRunner {
-    tasks: Vec<String>,
+    tasks: Vec<Task>,
 }
 
 impl NpmRunner {
@@ -14,9 +181,9 @@ impl NpmRunner {
         return NpmRunner { tasks: Vec::new() };
     }
 
-    fn read_package_json() -> Result<Vec<String>> {
+    fn read_package_json() -> Result<Vec<Task>> {
         let content = fs::read_to_string("package.json");
-        let mut script_names: Vec<String> = Vec::new();
+        let mut script_names: Vec<Task> = Vec::new();
 
         let content = match content {
             Ok(content) => content,
@@ -36,8 +203,13 @@ impl NpmRunner {
         };
 
         for (key, value) in scripts.iter() {
-            if let Value::String(_) = value {
-                script_names.push(key.to_string());
+            if let Value::String(command) = value {
+                let mut task = Task::new(key);
+                task.command = Some(command.to_string());
+                task.source = Some("package.json".into());
+                task.line = find_key_line(&content, &["scripts", key]);
+                task.flags = wrapped_cli_flags(command);
+                script_names.push(task);
             }
         }
 
@@ -50,37 +222,85 @@ impl Runner for NpmRunner {
         return "package.json";
     }
 
-    fn tasks(&self) -> &Vec<String> {
+    fn tasks(&self) -> &Vec<Task> {
         return &self.tasks;
     }
 
+    fn tasks_mut(&mut self) -> &mut Vec<Task> {
+        return &mut self.tasks;
+    }
+
     fn load(&mut self) -> Result<()> {
         let scripts = NpmRunner::read_package_json().context("Failed to read package.json")?;
         self.tasks = scripts;
         return Ok(());
     }
 
-    fn run(&self, task: &str, args: &[String]) -> Result<i32> {
-        // Detect pnpm usage from the pnpm lock file. The ../../ is for
-        // packges/* style monorepo
-        let is_pnpm =
-            Path::new("pnpm-lock.yaml").exists() || Path::new("../../pnpm-lock.yaml").exists();
+    fn explain(&self, _task: &str) -> Vec<(String, String)> {
+        let detected = match detect_package_manager() {
+            (manager, Some(lock_file)) => format!("{} (found {})", manager, lock_file),
+            (manager, None) => format!("{} (no lock file found)", manager),
+        };
+
+        return vec![("package manager".to_string(), detected)];
+    }
+
+    fn command(&self, task: &str, args: &[String]) -> Result<Command> {
+        let (manager, _) = detect_package_manager();
 
-        if is_pnpm {
-            eprintln!("[rt] Using pnpnm");
-            let mut pnpm = Command::new("pnpm");
-            return self.execute(pnpm.arg("run").arg(task).args(args));
+        let mut command = Command::new(manager);
+        command.arg("run").arg(task);
+        if manager == "npm" {
+            command.arg("--");
         }
+        command.args(args);
 
-        let is_yarn1 = Path::new("yarn.lock").exists() || Path::new("../../yarn.lock").exists();
-        if is_yarn1 {
-            eprintln!("[rt] Using  yarn");
-            let mut yarn = Command::new("yarn");
-            return self.execute(yarn.arg("run").arg(task).args(args));
-        }
+        return Ok(command);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_bin() {
+        let dir = env::temp_dir().join(format!("rt-bin-test-{}", std::process::id()));
+        let bin = dir.join("node_modules/.bin");
+        fs::create_dir_all(&bin).unwrap();
+        fs::create_dir_all(dir.join("packages/app")).unwrap();
+
+        assert_eq!(find_bin(&dir.join("packages/app"), "jake"), None);
+
+        fs::write(bin.join("jake"), "").unwrap();
+        assert_eq!(
+            find_bin(&dir.join("packages/app"), "jake"),
+            Some(bin.join("jake"))
+        );
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    fn flag_names(script: &str) -> Vec<String> {
+        return wrapped_cli_flags(script)
+            .into_iter()
+            .map(|f| f.flag)
+            .collect();
+    }
 
-        eprintln!("[rt] Using npm");
-        let mut npm = Command::new("npm");
-        return self.execute(npm.arg("run").arg(task).arg("--").args(args));
+    #[test]
+    fn test_wrapped_cli_flags() {
+        assert_eq!(
+            flag_names("prettier ."),
+            vec!["--write", "--check", "--list-different"]
+        );
+        assert_eq!(
+            flag_names("NODE_ENV=test cross-env CI=1 npx jest")[0],
+            "--watch"
+        );
+        assert_eq!(flag_names("tsc && eslint src")[0], "--fix");
+        assert_eq!(flag_names("./node_modules/.bin/tsc -p .")[1], "--noEmit");
+        assert!(flag_names("eslint src && node build.js").is_empty());
+        assert!(flag_names("").is_empty());
     }
 }
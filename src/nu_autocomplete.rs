@@ -0,0 +1,66 @@
+use crate::runner::Runner;
+use crate::zsh_autocomplete::{current_word, get_completion_items, task_summary, CompletionItems};
+use serde_json::json;
+
+fn get_nu_completion_code(items: &CompletionItems, word: &str) -> String {
+    let records: Vec<serde_json::Value> = match items {
+        CompletionItems::Files => return "null".to_string(),
+        CompletionItems::Tasks(tasks) => tasks
+            .iter()
+            .filter(|(_, task)| task.name.starts_with(word))
+            .map(|(name, task)| {
+                json!({
+                    "value": task.name,
+                    "description": task_summary(name, task),
+                })
+            })
+            .collect(),
+        CompletionItems::Args(args) => args
+            .iter()
+            .filter(|(value, _)| value.starts_with(word))
+            .map(|(value, description)| {
+                json!({
+                    "value": value,
+                    "description": description,
+                })
+            })
+            .collect(),
+    };
+
+    return serde_json::Value::Array(records).to_string();
+}
+
+pub fn get_nu_autocompletion(runners: &Vec<Box<dyn Runner>>, buffer: &str) -> String {
+    let items = get_completion_items(runners, buffer, "");
+    return get_nu_completion_code(&items, &current_word(buffer));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_runner::{deploy_task, TestRunner};
+
+    fn runners() -> Vec<Box<dyn Runner>> {
+        let tasks = vec!["foo".to_string(), "bar".to_string()];
+        let mut runner = TestRunner::new("runner1".to_string(), tasks);
+        runner.tasks.push(deploy_task());
+        return vec![Box::new(runner)];
+    }
+
+    #[test]
+    fn test_task_records() {
+        assert_eq!(
+            get_nu_autocompletion(&runners(), "rt f"),
+            r#"[{"description":"from runner1","value":"foo"}]"#
+        );
+        assert_eq!(
+            get_nu_autocompletion(&runners(), "rt deploy "),
+            r#"[{"description":"env","value":"prod"}]"#
+        );
+    }
+
+    #[test]
+    fn test_files_fall_back_to_null() {
+        assert_eq!(get_nu_autocompletion(&runners(), "rt foo "), "null");
+    }
+}
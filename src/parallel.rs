@@ -0,0 +1,241 @@
+use crate::signals;
+use std::env;
+use std::io::{self, BufRead, BufReader, IsTerminal, Read, Write};
+use std::os::unix::process::{CommandExt, ExitStatusExt};
+use std::process::{Command, ExitStatus, Stdio};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
+
+/// ANSI colors cycled through for the output prefixes
+const COLORS: [u8; 6] = [36, 33, 32, 35, 34, 31];
+
+pub struct Job {
+    pub label: String,
+    pub command: Command,
+}
+
+fn prefix(label: &str, width: usize, color: Option<u8>) -> String {
+    let padded = format!("{:width$}", label);
+
+    return match color {
+        Some(color) => format!("\x1b[{}m{} |\x1b[0m ", color, padded),
+        None => format!("{} | ", padded),
+    };
+}
+
+/// Exit code of a finished process. Processes killed by a signal get 128 +
+/// the signal number like in shells.
+pub fn exit_code(status: ExitStatus) -> i32 {
+    return status
+        .code()
+        .or_else(|| status.signal().map(|signal| 128 + signal))
+        .unwrap_or(1);
+}
+
+/// Copy the output line by line with the prefix. Each line is written while
+/// holding the lock so lines from different tasks are not mixed.
+fn copy_prefixed(reader: impl Read, prefix: &str, to_stderr: bool) {
+    let mut reader = BufReader::new(reader);
+    let mut line: Vec<u8> = Vec::new();
+
+    loop {
+        line.clear();
+        match reader.read_until(b'\n', &mut line) {
+            Ok(0) | Err(_) => break,
+            Ok(_) => {}
+        }
+
+        if !line.ends_with(b"\n") {
+            line.push(b'\n');
+        }
+
+        let mut out: Box<dyn Write> = if to_stderr {
+            Box::new(io::stderr().lock())
+        } else {
+            Box::new(io::stdout().lock())
+        };
+
+        out.write_all(prefix.as_bytes()).ok();
+        out.write_all(&line).ok();
+        out.flush().ok();
+    }
+}
+
+/// Run the jobs concurrently with prefixed output. Returns 0 when every job
+/// succeeded, otherwise the exit code of the first failed job in the given
+/// order so the result does not depend on timing. Each job gets its own
+/// process group which SIGINT, SIGTERM and SIGHUP are forwarded to, rt then
+/// waits for all jobs and returns 128 + the signal number.
+pub fn run_parallel(jobs: Vec<Job>) -> i32 {
+    let color = io::stdout().is_terminal() && env::var_os("NO_COLOR").is_none();
+    let width = jobs
+        .iter()
+        .map(|job| job.label.chars().count())
+        .max()
+        .unwrap_or(0);
+
+    let dispositions = signals::listen();
+    let (sender, receiver) = mpsc::channel();
+    let mut labels: Vec<String> = Vec::new();
+    let mut groups: Vec<Option<libc::pid_t>> = Vec::new();
+
+    for (index, mut job) in jobs.into_iter().enumerate() {
+        let prefix = prefix(
+            &job.label,
+            width,
+            color.then(|| COLORS[index % COLORS.len()]),
+        );
+        labels.push(job.label);
+        groups.push(None);
+
+        let spawned = job
+            .command
+            .process_group(0)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn();
+
+        let mut child = match spawned {
+            Ok(child) => child,
+            Err(e) => {
+                eprintln!(
+                    "{}Failed to spawn {:?}: {}",
+                    prefix,
+                    job.command.get_program(),
+                    e
+                );
+                sender.send((index, 127)).ok();
+                continue;
+            }
+        };
+
+        groups[index] = Some(child.id() as libc::pid_t);
+        let sender = sender.clone();
+        let stdout = child.stdout.take();
+        let stderr = child.stderr.take();
+
+        thread::spawn(move || {
+            let stderr_prefix = prefix.clone();
+            let stderr_copy = thread::spawn(move || {
+                if let Some(stderr) = stderr {
+                    copy_prefixed(stderr, &stderr_prefix, true);
+                }
+            });
+
+            if let Some(stdout) = stdout {
+                copy_prefixed(stdout, &prefix, false);
+            }
+            stderr_copy.join().ok();
+
+            let code = child.wait().map(exit_code).unwrap_or(1);
+            sender.send((index, code)).ok();
+        });
+    }
+
+    drop(sender);
+
+    let mut first_failure: Option<(usize, i32)> = None;
+    let mut stopped_by: Option<i32> = None;
+
+    loop {
+        // Signals are forwarded from here as the handler knows a single group
+        if let Some(signal) = signals::take_pending() {
+            for group in groups.iter().flatten() {
+                signals::kill_group(*group, signal);
+            }
+            stopped_by = Some(signal);
+        }
+
+        let (index, code) = match receiver.recv_timeout(Duration::from_millis(50)) {
+            Ok(finished) => finished,
+            Err(RecvTimeoutError::Timeout) => continue,
+            Err(RecvTimeoutError::Disconnected) => break,
+        };
+        groups[index] = None;
+        eprintln!("[rt] {} exited with code {}", labels[index], code);
+
+        if code != 0 && first_failure.is_none_or(|(first, _)| index < first) {
+            first_failure = Some((index, code));
+        }
+    }
+
+    signals::restore(dispositions);
+
+    if let Some(signal) = stopped_by {
+        eprintln!("[rt] Stopped by signal {}", signal);
+        return 128 + signal;
+    }
+    return first_failure.map_or(0, |(_, code)| code);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shell_job(label: &str, script: &str) -> Job {
+        let mut command = Command::new("sh");
+        command.arg("-c").arg(script);
+        return Job {
+            label: label.to_string(),
+            command,
+        };
+    }
+
+    #[test]
+    fn test_prefix() {
+        assert_eq!(prefix("api", 5, None), "api   | ");
+        assert_eq!(prefix("dev", 3, Some(36)), "\x1b[36mdev |\x1b[0m ");
+    }
+
+    #[test]
+    fn test_aggregated_exit_code() {
+        let _lock = signals::TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+        assert_eq!(
+            run_parallel(vec![shell_job("a", "echo a"), shell_job("b", "true")]),
+            0
+        );
+        assert_eq!(
+            run_parallel(vec![shell_job("a", "exit 0"), shell_job("b", "exit 3")]),
+            3
+        );
+        assert_eq!(run_parallel(vec![shell_job("a", "kill -9 $$")]), 137);
+    }
+
+    #[test]
+    fn test_exit_code_by_task_order() {
+        let _lock = signals::TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+        assert_eq!(
+            run_parallel(vec![
+                shell_job("a", "sleep 0.3; exit 4"),
+                shell_job("b", "exit 5"),
+            ]),
+            4
+        );
+    }
+
+    #[test]
+    fn test_signal_forwarded_to_all_jobs() {
+        let _lock = signals::TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+        let sender = thread::spawn(|| {
+            thread::sleep(Duration::from_millis(300));
+            unsafe {
+                libc::kill(libc::getpid(), libc::SIGTERM);
+            }
+        });
+        let started = std::time::Instant::now();
+        assert_eq!(
+            run_parallel(vec![
+                shell_job("a", "sleep 5"),
+                shell_job("b", "trap 'exit 0' TERM; sleep 5 & wait"),
+            ]),
+            143
+        );
+        assert!(started.elapsed() < Duration::from_secs(4));
+        sender.join().unwrap();
+    }
+}
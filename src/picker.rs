@@ -0,0 +1,358 @@
+use crate::runner::Task;
+use anyhow::{Context, Result};
+use crossterm::{
+    cursor,
+    event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    queue,
+    style::{Attribute, Print, SetAttribute},
+    terminal::{self, ClearType},
+};
+use std::io::{self, Write};
+
+/// Maximum number of lines used for the command preview
+const PREVIEW_LINES: u16 = 8;
+
+pub struct PickItem<'a> {
+    pub runner: &'a str,
+    pub task: &'a Task,
+}
+
+/// Score a case-insensitive subsequence match. Consecutive characters and
+/// matches at word starts score higher.
+fn fuzzy_score(query: &str, text: &str) -> Option<usize> {
+    let text: Vec<char> = text.chars().collect();
+    let mut score = 0;
+    let mut position = 0;
+    let mut previous_match: Option<usize> = None;
+
+    for query_char in query.chars().flat_map(char::to_lowercase) {
+        let offset = text[position..]
+            .iter()
+            .position(|c| c.to_lowercase().eq(std::iter::once(query_char)))?;
+        let index = position + offset;
+
+        score += 1;
+        if previous_match.is_some_and(|previous| previous + 1 == index) {
+            score += 2;
+        }
+        if index == 0 || !text[index - 1].is_alphanumeric() {
+            score += 3;
+        }
+
+        previous_match = Some(index);
+        position = index + 1;
+    }
+
+    return Some(score);
+}
+
+/// Indexes of the items matching every word of the query, best first.
+/// Matches in the task name are preferred over the runner and description.
+fn filter_items(items: &[PickItem], query: &str) -> Vec<usize> {
+    let mut scored: Vec<(usize, usize)> = Vec::new();
+
+    for (index, item) in items.iter().enumerate() {
+        let haystack = format!(
+            "{} {} {}",
+            item.task.name,
+            item.runner,
+            item.task.description.as_deref().unwrap_or_default()
+        );
+
+        let score = query.split_whitespace().try_fold(0, |total, word| {
+            let score = fuzzy_score(word, &item.task.name)
+                .map(|score| score * 2)
+                .or_else(|| fuzzy_score(word, &haystack))?;
+            return Some(total + score);
+        });
+
+        if let Some(score) = score {
+            scored.push((index, score));
+        }
+    }
+
+    // Stable sort keeps the listing order for equal scores
+    scored.sort_by(|a, b| b.1.cmp(&a.1));
+    return scored.into_iter().map(|(index, _)| index).collect();
+}
+
+/// Preview lines for the selected task
+fn preview_lines(item: &PickItem) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+
+    if let Some(command) = &item.task.command {
+        lines.extend(command.lines().map(|line| line.to_string()));
+    }
+
+    if !item.task.deps.is_empty() {
+        lines.push(format!("# depends on {}", item.task.deps.join(", ")));
+    }
+
+    if let Some(source) = &item.task.source {
+        let location = match item.task.line {
+            Some(line) => format!("{}:{}", source.display(), line),
+            None => source.display().to_string(),
+        };
+        lines.push(format!("# {}", location));
+    }
+
+    if lines.is_empty() {
+        lines.push(format!("# from {}", item.runner));
+    }
+
+    return lines;
+}
+
+fn truncate(text: &str, width: u16) -> String {
+    return text
+        .chars()
+        .map(|c| if c.is_control() { ' ' } else { c })
+        .take(width as usize)
+        .collect();
+}
+
+/// Restores the terminal when dropped, also on errors
+struct TerminalGuard;
+
+impl TerminalGuard {
+    fn new() -> Result<Self> {
+        terminal::enable_raw_mode().context("Failed to enable raw mode")?;
+        let guard = TerminalGuard;
+        crossterm::execute!(io::stderr(), terminal::EnterAlternateScreen)?;
+        return Ok(guard);
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        crossterm::execute!(io::stderr(), terminal::LeaveAlternateScreen).ok();
+        terminal::disable_raw_mode().ok();
+    }
+}
+
+struct Picker<'a> {
+    items: &'a [PickItem<'a>],
+    query: String,
+    matches: Vec<usize>,
+    selected: usize,
+    scroll: usize,
+}
+
+impl Picker<'_> {
+    fn update_matches(&mut self) {
+        self.matches = filter_items(self.items, &self.query);
+        self.selected = 0;
+        self.scroll = 0;
+    }
+
+    fn move_selection(&mut self, down: bool) {
+        if down && self.selected + 1 < self.matches.len() {
+            self.selected += 1;
+        } else if !down && self.selected > 0 {
+            self.selected -= 1;
+        }
+    }
+
+    fn draw(&mut self, out: &mut impl Write) -> Result<()> {
+        let (width, height) = terminal::size()?;
+        let preview_height = PREVIEW_LINES.min(height / 3);
+        let list_height = height.saturating_sub(preview_height + 3) as usize;
+
+        if self.selected < self.scroll {
+            self.scroll = self.selected;
+        } else if list_height > 0 && self.selected >= self.scroll + list_height {
+            self.scroll = self.selected + 1 - list_height;
+        }
+
+        queue!(out, terminal::Clear(ClearType::All))?;
+
+        let name_width = self
+            .items
+            .iter()
+            .map(|item| item.task.name.chars().count())
+            .max()
+            .unwrap_or(0);
+
+        let visible = self.scroll..self.matches.len().min(self.scroll + list_height);
+
+        for (row, position) in visible.enumerate() {
+            let index = self.matches[position];
+            let item = &self.items[index];
+            let line = format!(
+                "{} {:name_width$}  {}  {}",
+                if position == self.selected { ">" } else { " " },
+                item.task.name,
+                item.runner,
+                item.task.description.as_deref().unwrap_or_default(),
+            );
+
+            queue!(out, cursor::MoveTo(0, row as u16 + 2))?;
+            if position == self.selected {
+                queue!(
+                    out,
+                    SetAttribute(Attribute::Reverse),
+                    Print(truncate(&line, width)),
+                    SetAttribute(Attribute::Reset)
+                )?;
+            } else {
+                queue!(out, Print(truncate(&line, width)))?;
+            }
+        }
+
+        let preview_top = height.saturating_sub(preview_height + 1);
+        queue!(
+            out,
+            cursor::MoveTo(0, preview_top),
+            SetAttribute(Attribute::Dim),
+            Print("─".repeat(width as usize)),
+            SetAttribute(Attribute::Reset)
+        )?;
+
+        if let Some(index) = self.matches.get(self.selected) {
+            let lines = preview_lines(&self.items[*index]);
+            for (row, line) in lines.iter().take(preview_height as usize).enumerate() {
+                queue!(
+                    out,
+                    cursor::MoveTo(0, preview_top + 1 + row as u16),
+                    Print(truncate(line, width))
+                )?;
+            }
+        }
+
+        let prompt = format!("> {}", self.query);
+        queue!(
+            out,
+            cursor::MoveTo(0, 1),
+            SetAttribute(Attribute::Dim),
+            Print(format!("  {}/{}", self.matches.len(), self.items.len())),
+            SetAttribute(Attribute::Reset),
+            cursor::MoveTo(0, 0),
+            Print(truncate(&prompt, width)),
+        )?;
+
+        out.flush()?;
+        return Ok(());
+    }
+}
+
+/// Let the user fuzzy find a task. Returns the index of the picked item or
+/// None when cancelled.
+pub fn pick(items: &[PickItem]) -> Result<Option<usize>> {
+    let _guard = TerminalGuard::new()?;
+    let mut out = io::stderr();
+
+    let mut picker = Picker {
+        items,
+        query: String::new(),
+        matches: Vec::new(),
+        selected: 0,
+        scroll: 0,
+    };
+    picker.update_matches();
+
+    loop {
+        picker.draw(&mut out)?;
+
+        let Event::Key(KeyEvent {
+            code,
+            modifiers,
+            kind: KeyEventKind::Press,
+            ..
+        }) = event::read()?
+        else {
+            continue;
+        };
+
+        let ctrl = modifiers.contains(KeyModifiers::CONTROL);
+
+        match code {
+            KeyCode::Esc => return Ok(None),
+            KeyCode::Char('c' | 'g' | 'd') if ctrl => return Ok(None),
+            KeyCode::Enter => return Ok(picker.matches.get(picker.selected).copied()),
+            KeyCode::Up | KeyCode::BackTab => picker.move_selection(false),
+            KeyCode::Down | KeyCode::Tab => picker.move_selection(true),
+            KeyCode::Char('p' | 'k') if ctrl => picker.move_selection(false),
+            KeyCode::Char('n' | 'j') if ctrl => picker.move_selection(true),
+            KeyCode::Char('u') if ctrl => {
+                picker.query.clear();
+                picker.update_matches();
+            }
+            KeyCode::Backspace => {
+                picker.query.pop();
+                picker.update_matches();
+            }
+            KeyCode::Char(c) if !ctrl => {
+                picker.query.push(c);
+                picker.update_matches();
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fuzzy_score() {
+        assert!(fuzzy_score("bld", "build").is_some());
+        assert!(fuzzy_score("BLD", "build").is_some());
+        assert!(fuzzy_score("dlb", "build").is_none());
+        assert_eq!(fuzzy_score("", "build"), Some(0));
+
+        // Word starts and consecutive characters score higher
+        assert!(fuzzy_score("dm", "db:migrate") > fuzzy_score("dm", "seedmore"));
+        assert!(fuzzy_score("test", "test:unit") > fuzzy_score("test", "the-estate"));
+    }
+
+    #[test]
+    fn test_filter_items() {
+        let mut lint = Task::new("lint");
+        lint.description = Some("Run the build checks".to_string());
+        let tasks = [Task::new("test"), Task::new("build"), lint];
+        let items: Vec<PickItem> = tasks
+            .iter()
+            .map(|task| PickItem {
+                runner: "package.json",
+                task,
+            })
+            .collect();
+
+        assert_eq!(filter_items(&items, ""), vec![0, 1, 2]);
+        assert_eq!(filter_items(&items, "build"), vec![1, 2]);
+        assert_eq!(filter_items(&items, "li checks"), vec![2]);
+        assert_eq!(filter_items(&items, "json t"), vec![0, 2]);
+        assert!(filter_items(&items, "xyz").is_empty());
+    }
+
+    #[test]
+    fn test_preview_lines() {
+        let mut task = Task::new("build");
+        task.command = Some("tsc\nvite build".to_string());
+        task.source = Some("package.json".into());
+        task.line = Some(3);
+        task.deps = vec!["clean".to_string()];
+
+        let item = PickItem {
+            runner: "package.json",
+            task: &task,
+        };
+        assert_eq!(
+            preview_lines(&item),
+            vec![
+                "tsc",
+                "vite build",
+                "# depends on clean",
+                "# package.json:3"
+            ]
+        );
+
+        let task = Task::new("deploy");
+        let item = PickItem {
+            runner: "Makefile",
+            task: &task,
+        };
+        assert_eq!(preview_lines(&item), vec!["# from Makefile"]);
+    }
+}
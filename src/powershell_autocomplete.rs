@@ -0,0 +1,97 @@
+use crate::runner::Runner;
+use crate::zsh_autocomplete::{current_word, get_completion_items, task_summary, CompletionItems};
+
+/// Control characters would break the `value\ttooltip` line format
+fn line_escape(text: &str) -> String {
+    return text.replace(|c: char| c.is_control(), " ");
+}
+
+/// A `value\ttooltip` line. The completer script builds the completion
+/// results from these so no text from the project files is ever evaluated.
+fn completion_line(value: &str, description: &str) -> String {
+    // The tooltip must not be empty
+    let tooltip = if description.is_empty() {
+        value
+    } else {
+        description
+    };
+
+    return format!("{}\t{}\n", line_escape(value), line_escape(tooltip));
+}
+
+fn get_powershell_completion_code(items: &CompletionItems, word: &str) -> String {
+    let mut out = String::new();
+
+    match items {
+        CompletionItems::Files => {}
+        CompletionItems::Tasks(tasks) => {
+            for (name, task) in tasks {
+                if task.name.starts_with(word) {
+                    out.push_str(&completion_line(&task.name, &task_summary(name, task)));
+                }
+            }
+        }
+        CompletionItems::Args(args) => {
+            for (value, description) in args {
+                if value.starts_with(word) {
+                    out.push_str(&completion_line(value, description));
+                }
+            }
+        }
+    }
+
+    return out;
+}
+
+pub fn get_powershell_autocompletion(runners: &Vec<Box<dyn Runner>>, buffer: &str) -> String {
+    let items = get_completion_items(runners, buffer, "");
+    return get_powershell_completion_code(&items, &current_word(buffer));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runner::Task;
+    use crate::test_runner::{deploy_task, TestRunner};
+
+    fn runners() -> Vec<Box<dyn Runner>> {
+        let mut runner = TestRunner::new("runner1".to_string(), vec!["foo".to_string()]);
+        runner.tasks.push(deploy_task());
+        let mut build = Task::new("build");
+        build.description = Some("Build the user's app".to_string());
+        runner.tasks.push(build);
+
+        return vec![Box::new(runner)];
+    }
+
+    #[test]
+    fn test_completion_results() {
+        assert_eq!(
+            get_powershell_autocompletion(&runners(), "rt b"),
+            "build\tBuild the user's app\n"
+        );
+        assert_eq!(
+            get_powershell_autocompletion(&runners(), "rt deploy "),
+            "prod\tenv\n"
+        );
+    }
+
+    #[test]
+    fn test_quotes_are_plain_data() {
+        let mut runner = TestRunner::new("runner1".to_string(), Vec::new());
+        let mut task = Task::new("x\u{2019}); Remove-Item ~ #");
+        task.description = Some("Don't\tbreak\nlines".to_string());
+        runner.tasks.push(task);
+        let runners: Vec<Box<dyn Runner>> = vec![Box::new(runner)];
+
+        assert_eq!(
+            get_powershell_autocompletion(&runners, "rt x"),
+            "x\u{2019}); Remove-Item ~ #\tDon't break lines\n"
+        );
+    }
+
+    #[test]
+    fn test_files_print_nothing() {
+        assert_eq!(get_powershell_autocompletion(&runners(), "rt foo "), "");
+    }
+}
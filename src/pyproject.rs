@@ -0,0 +1,364 @@
+use super::runner::{find_table_key_line, Runner, Task};
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::process::Command;
+use std::{fs, io::ErrorKind};
+use toml::Value;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum PyTool {
+    Poe,
+    Pdm,
+    Hatch,
+    Rye,
+}
+
+#[derive(Debug, PartialEq)]
+struct PyTask {
+    task: Task,
+    tool: PyTool,
+}
+
+fn table_string(value: &Value, key: &str) -> Option<String> {
+    return value.get(key)?.as_str().map(|s| s.to_string());
+}
+
+/// Command text of a script in any of the string, list or table forms
+fn script_command(value: &Value) -> Option<String> {
+    return match value {
+        Value::String(command) => Some(command.clone()),
+        Value::Array(items) => {
+            let lines: Vec<&str> = items.iter().filter_map(|item| item.as_str()).collect();
+            if lines.is_empty() {
+                None
+            } else {
+                Some(lines.join("\n"))
+            }
+        }
+        Value::Table(_) => ["cmd", "shell", "script", "call", "composite", "sequence"]
+            .iter()
+            .find_map(|key| value.get(*key).and_then(script_command)),
+        _ => None,
+    };
+}
+
+fn py_task(content: &str, section: &str, key: &str, name: String, value: &Value) -> Task {
+    let mut task = Task::new(name);
+    task.description = table_string(value, "help");
+    task.command = script_command(value);
+    task.source = Some("pyproject.toml".into());
+    task.line = find_table_key_line(content, section, key);
+    return task;
+}
+
+fn parse_pyproject(content: &str) -> Result<Vec<PyTask>> {
+    let pyproject: Value = toml::from_str(content).context("Failed to parse TOML")?;
+    let mut tasks: Vec<PyTask> = Vec::new();
+
+    let Some(tool) = pyproject.get("tool") else {
+        return Ok(tasks);
+    };
+
+    // https://poethepoet.natn.io/tasks/index.html
+    if let Some(poe_tasks) = tool
+        .get("poe")
+        .and_then(|poe| poe.get("tasks"))
+        .and_then(|t| t.as_table())
+    {
+        for (name, value) in poe_tasks {
+            let mut task = py_task(content, "tool.poe.tasks", name, name.to_string(), value);
+            // Tasks starting with an underscore are private in poe
+            task.hidden = name.starts_with('_');
+
+            tasks.push(PyTask {
+                task,
+                tool: PyTool::Poe,
+            });
+        }
+    }
+
+    // https://pdm-project.org/latest/usage/scripts/
+    if let Some(pdm_scripts) = tool
+        .get("pdm")
+        .and_then(|pdm| pdm.get("scripts"))
+        .and_then(|s| s.as_table())
+    {
+        for (name, script) in pdm_scripts {
+            // "_" holds options shared by all scripts
+            if name == "_" {
+                continue;
+            }
+
+            tasks.push(PyTask {
+                task: py_task(content, "tool.pdm.scripts", name, name.to_string(), script),
+                tool: PyTool::Pdm,
+            });
+        }
+    }
+
+    // https://hatch.pypa.io/latest/config/environment/overview/#scripts
+    if let Some(envs) = tool
+        .get("hatch")
+        .and_then(|hatch| hatch.get("envs"))
+        .and_then(|e| e.as_table())
+    {
+        for (env, config) in envs {
+            let Some(scripts) = config.get("scripts").and_then(|s| s.as_table()) else {
+                continue;
+            };
+
+            let section = format!("tool.hatch.envs.{}.scripts", env);
+
+            for (key, script) in scripts {
+                let name = if env == "default" {
+                    key.to_string()
+                } else {
+                    format!("{}:{}", env, key)
+                };
+
+                tasks.push(PyTask {
+                    task: py_task(content, &section, key, name, script),
+                    tool: PyTool::Hatch,
+                });
+            }
+        }
+    }
+
+    // https://rye.astral.sh/guide/pyproject/#toolryescripts
+    if let Some(rye_scripts) = tool
+        .get("rye")
+        .and_then(|rye| rye.get("scripts"))
+        .and_then(|s| s.as_table())
+    {
+        for (name, script) in rye_scripts {
+            tasks.push(PyTask {
+                task: py_task(content, "tool.rye.scripts", name, name.to_string(), script),
+                tool: PyTool::Rye,
+            });
+        }
+    }
+
+    let mut seen: Vec<String> = Vec::new();
+    tasks.retain(|py_task| {
+        if seen.contains(&py_task.task.name) {
+            return false;
+        }
+        seen.push(py_task.task.name.clone());
+        return true;
+    });
+
+    return Ok(tasks);
+}
+
+pub struct PyprojectRunner {
+    tasks: Vec<Task>,
+    tools: HashMap<String, PyTool>,
+}
+
+impl PyprojectRunner {
+    pub fn new() -> Self {
+        return PyprojectRunner {
+            tasks: Vec::new(),
+            tools: HashMap::new(),
+        };
+    }
+
+    fn read_pyproject() -> Result<Vec<PyTask>> {
+        let content = match fs::read_to_string("pyproject.toml") {
+            Ok(content) => content,
+            Err(e) => {
+                if ErrorKind::NotFound == e.kind() {
+                    return Ok(Vec::new());
+                }
+
+                anyhow::bail!(e);
+            }
+        };
+
+        return parse_pyproject(&content);
+    }
+}
+
+impl Runner for PyprojectRunner {
+    fn name(&self) -> &'static str {
+        return "pyproject.toml";
+    }
+
+    fn tasks(&self) -> &Vec<Task> {
+        return &self.tasks;
+    }
+
+    fn tasks_mut(&mut self) -> &mut Vec<Task> {
+        return &mut self.tasks;
+    }
+
+    fn load(&mut self) -> Result<()> {
+        let py_tasks =
+            PyprojectRunner::read_pyproject().context("Failed to read pyproject.toml")?;
+
+        self.tasks.clear();
+        self.tools.clear();
+
+        for py_task in py_tasks {
+            self.tools.insert(py_task.task.name.clone(), py_task.tool);
+            self.tasks.push(py_task.task);
+        }
+
+        return Ok(());
+    }
+
+    fn command(&self, task: &str, args: &[String]) -> Result<Command> {
+        let tool = self.tools.get(task).copied().unwrap_or(PyTool::Poe);
+
+        let command = match tool {
+            PyTool::Poe => {
+                let mut poe = Command::new("poe");
+                poe.arg(task).args(args);
+                poe
+            }
+            PyTool::Pdm => {
+                let mut pdm = Command::new("pdm");
+                pdm.arg("run").arg(task).args(args);
+                pdm
+            }
+            PyTool::Hatch => {
+                let mut hatch = Command::new("hatch");
+                hatch.arg("run").arg(task).args(args);
+                hatch
+            }
+            PyTool::Rye => {
+                let mut rye = Command::new("rye");
+                rye.arg("run").arg(task).args(args);
+                rye
+            }
+        };
+
+        return Ok(command);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary(tasks: &[PyTask]) -> Vec<(&str, PyTool, Option<&str>)> {
+        return tasks
+            .iter()
+            .map(|t| (t.task.name.as_str(), t.tool, t.task.description.as_deref()))
+            .collect();
+    }
+
+    #[test]
+    fn test_parse_poe_tasks() {
+        let content = r#"
+[tool.poe.tasks]
+test = "pytest"
+lint = { cmd = "ruff check .", help = "Lint the code" }
+_private = "echo"
+check = ["lint", "test"]
+
+[tool.poe.tasks.serve]
+script = "app:serve"
+help = "Start the server"
+"#;
+        let tasks = parse_pyproject(content).unwrap();
+
+        assert_eq!(
+            summary(&tasks),
+            vec![
+                ("_private", PyTool::Poe, None),
+                ("check", PyTool::Poe, None),
+                ("lint", PyTool::Poe, Some("Lint the code")),
+                ("serve", PyTool::Poe, Some("Start the server")),
+                ("test", PyTool::Poe, None),
+            ]
+        );
+
+        assert!(tasks[0].task.hidden);
+        assert!(!tasks[1].task.hidden);
+        assert_eq!(tasks[1].task.command.as_deref(), Some("lint\ntest"));
+        assert_eq!(tasks[2].task.command.as_deref(), Some("ruff check ."));
+        assert_eq!(tasks[2].task.line, Some(4));
+        assert_eq!(tasks[3].task.command.as_deref(), Some("app:serve"));
+        assert_eq!(tasks[3].task.line, Some(8));
+        assert_eq!(tasks[4].task.line, Some(3));
+    }
+
+    #[test]
+    fn test_parse_pdm_scripts() {
+        let content = r#"
+[tool.pdm.scripts]
+_.env_file = ".env"
+start = "flask run -p 54321"
+migrate = { cmd = "flask db upgrade", help = "Run migrations" }
+"#;
+        let tasks = parse_pyproject(content).unwrap();
+
+        assert_eq!(
+            summary(&tasks),
+            vec![
+                ("migrate", PyTool::Pdm, Some("Run migrations")),
+                ("start", PyTool::Pdm, None),
+            ]
+        );
+        assert_eq!(tasks[1].task.command.as_deref(), Some("flask run -p 54321"));
+        assert_eq!(tasks[1].task.line, Some(4));
+    }
+
+    #[test]
+    fn test_parse_hatch_and_rye_scripts() {
+        let content = r#"
+[tool.hatch.envs.default.scripts]
+cov = "pytest --cov"
+
+[tool.hatch.envs.docs.scripts]
+build = "mkdocs build"
+
+[tool.rye.scripts]
+devserver = { cmd = "flask run --debug" }
+"#;
+        let tasks = parse_pyproject(content).unwrap();
+        let names: Vec<(&str, PyTool)> = tasks
+            .iter()
+            .map(|t| (t.task.name.as_str(), t.tool))
+            .collect();
+
+        assert_eq!(
+            names,
+            vec![
+                ("cov", PyTool::Hatch),
+                ("docs:build", PyTool::Hatch),
+                ("devserver", PyTool::Rye),
+            ]
+        );
+        assert_eq!(tasks[1].task.line, Some(6));
+        assert_eq!(tasks[2].task.command.as_deref(), Some("flask run --debug"));
+    }
+
+    #[test]
+    fn test_first_tool_wins_on_duplicates() {
+        let content = r#"
+[tool.poe.tasks]
+test = "pytest"
+
+[tool.pdm.scripts]
+test = "pytest -x"
+"#;
+        let tasks = parse_pyproject(content).unwrap();
+
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].tool, PyTool::Poe);
+    }
+
+    #[test]
+    fn test_parse_without_tools() {
+        let content = "[project]\nname = \"app\"\n";
+
+        assert!(parse_pyproject(content).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_parse_invalid_toml() {
+        assert!(parse_pyproject("[tool.poe.tasks\n").is_err());
+    }
+}
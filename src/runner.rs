@@ -1,22 +1,324 @@
-use anyhow::{anyhow, Context, Result};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
 use std::process::Command;
 
+/// A single runnable task as discovered by a runner
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Task {
+    pub name: String,
+    pub description: Option<String>,
+    /// The underlying command text, eg. the script string in package.json
+    pub command: Option<String>,
+    /// File the task was defined in, relative to the project directory
+    pub source: Option<PathBuf>,
+    /// 1-based line number in the source file
+    pub line: Option<usize>,
+    /// Private tasks can be run but are not listed or completed
+    pub hidden: bool,
+    /// Positional arguments in order
+    pub params: Vec<TaskParam>,
+    /// Options accepted in any position, eg. `--watch` or `NAME=`
+    pub flags: Vec<TaskFlag>,
+    /// Globs of the files the task reads, relative to the project directory.
+    /// Watched by `rt --watch` when no glob is given.
+    pub inputs: Vec<String>,
+    /// Globs of the files the task writes. Ignored by `rt --watch`.
+    pub outputs: Vec<String>,
+    /// Tasks run before this one by the tool itself
+    pub deps: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TaskParam {
+    pub name: String,
+    pub default: Option<String>,
+    /// Takes the rest of the arguments. Only the last param can be variadic.
+    pub variadic: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TaskFlag {
+    pub flag: String,
+    pub description: Option<String>,
+}
+
+impl TaskFlag {
+    pub fn new(flag: impl Into<String>, description: Option<&str>) -> Self {
+        return TaskFlag {
+            flag: flag.into(),
+            description: description.map(|d| d.to_string()),
+        };
+    }
+
+    /// The flag up to and including `=`, eg. `ENV=` for `ENV=prod`
+    fn key(&self) -> &str {
+        return self.flag.split_inclusive('=').next().unwrap_or(&self.flag);
+    }
+}
+
+impl Task {
+    pub fn new(name: impl Into<String>) -> Self {
+        return Task {
+            name: name.into(),
+            ..Default::default()
+        };
+    }
+
+    /// Completion candidates as (value, description) pairs for the next
+    /// argument given the arguments already typed after the task name
+    pub fn arg_completions(&self, args: &[&str]) -> Vec<(String, String)> {
+        let mut completions: Vec<(String, String)> = Vec::new();
+
+        let is_flag = |arg: &&str| {
+            arg.starts_with('-') || self.flags.iter().any(|f| arg.starts_with(f.key()))
+        };
+        let position = args.iter().filter(|arg| !is_flag(arg)).count();
+
+        // Variadic parameters are always last and take the rest of the arguments
+        let param = self
+            .params
+            .get(position)
+            .or_else(|| self.params.last().filter(|p| p.variadic));
+
+        if let Some(TaskParam {
+            name,
+            default: Some(default),
+            ..
+        }) = param
+        {
+            completions.push((default.clone(), name.clone()));
+        }
+
+        for flag in &self.flags {
+            if args.iter().any(|arg| arg.starts_with(flag.key())) {
+                continue;
+            }
+
+            let description = flag.description.as_deref().unwrap_or("option");
+            completions.push((flag.flag.clone(), description.to_string()));
+        }
+
+        return completions;
+    }
+}
+
+/// Find the 1-based line where a JSON, YAML or TOML key is defined. Matches
+/// lines like `"key": ...`, `key: ...` and `key = ...`. Each key in the path
+/// is searched after the line of the previous one.
+pub fn find_key_line(content: &str, path: &[&str]) -> Option<usize> {
+    let mut start = 0;
+
+    for key in path {
+        let found = content
+            .lines()
+            .enumerate()
+            .skip(start)
+            .find(|(_, line)| is_key_line(line, key));
+
+        let (index, _) = found?;
+        start = index + 1;
+    }
+
+    if start == 0 {
+        return None;
+    }
+
+    return Some(start);
+}
+
+fn is_key_line(line: &str, key: &str) -> bool {
+    let line = line.trim_start();
+
+    let rest = match line.chars().next() {
+        Some(quote) if quote == '"' || quote == '\'' => line
+            .strip_prefix(quote)
+            .and_then(|rest| rest.strip_prefix(key))
+            .and_then(|rest| rest.strip_prefix(quote)),
+        _ => line.strip_prefix(key),
+    };
+
+    let Some(rest) = rest else {
+        return false;
+    };
+
+    let rest = rest.trim_start();
+    return rest.starts_with(':') || rest.starts_with('=');
+}
+
+/// Find the 1-based line of a TOML key either under the `[section]` header
+/// or as its own `[section.name]` table
+pub fn find_table_key_line(content: &str, section: &str, name: &str) -> Option<usize> {
+    let header = format!("[{}]", section);
+    let own_header = format!("[{}.{}]", section, name);
+    let mut in_section = false;
+
+    for (index, line) in content.lines().enumerate() {
+        let line = line.trim();
+
+        if line.starts_with('[') {
+            if line == own_header {
+                return Some(index + 1);
+            }
+            in_section = line == header;
+            continue;
+        }
+
+        if !in_section {
+            continue;
+        }
+
+        let key = line.split(['=', '.']).next().unwrap_or("").trim();
+        if key.trim_matches('"') == name {
+            return Some(index + 1);
+        }
+    }
+
+    return None;
+}
+
 pub trait Runner {
     fn name(&self) -> &str;
-    fn tasks(&self) -> &Vec<String>;
+    /// Files or directories whose presence marks a project root for this runner
+    fn marker_files(&self) -> Vec<&str> {
+        return vec![self.name()];
+    }
+    /// Files read while loading besides the marker files and task sources,
+    /// eg. includes. Missing files that would be read once created belong
+    /// here too. Changes to any of them invalidate the completion cache.
+    fn cache_files(&self) -> Vec<&str> {
+        return Vec::new();
+    }
+    fn tasks(&self) -> &Vec<Task>;
+    fn tasks_mut(&mut self) -> &mut Vec<Task>;
+    /// Replace the tasks with ones from the completion cache. Other runner
+    /// state is not restored so the runner is only usable for completion.
+    fn restore_tasks(&mut self, tasks: Vec<Task>) {
+        *self.tasks_mut() = tasks;
+    }
+    fn find_task(&self, name: &str) -> Option<&Task> {
+        return self.tasks().iter().find(|task| task.name == name);
+    }
+    /// Completion candidates as (value, description) pairs for the next
+    /// argument of a task. `args` are the arguments already typed after the
+    /// task name.
+    fn arg_completions(&self, task: &str, args: &[&str]) -> Vec<(String, String)> {
+        return match self.find_task(task) {
+            Some(task) => task.arg_completions(args),
+            None => Vec::new(),
+        };
+    }
     fn load(&mut self) -> Result<()>;
-    fn run(&self, task: &str, args: &[String]) -> Result<i32>;
+    /// Details shown by `rt --dry-run` as (label, value) pairs, eg. how the
+    /// package manager was chosen
+    fn explain(&self, _task: &str) -> Vec<(String, String)> {
+        return Vec::new();
+    }
+    /// The command running the task with the arguments
+    fn command(&self, task: &str, args: &[String]) -> Result<Command>;
+    fn run(&self, task: &str, args: &[String]) -> Result<i32> {
+        let mut cmd = self.command(task, args)?;
+        eprintln!("[rt] Using {}", cmd.get_program().to_string_lossy());
+        return self.execute(&mut cmd);
+    }
+    /// Run the command forwarding SIGINT, SIGTERM and SIGHUP to it. Tasks
+    /// killed by a signal exit with 128 + the signal number.
     fn execute(&self, cmd: &mut Command) -> Result<i32> {
-        let mut child = cmd
-            .spawn()
-            .with_context(|| format!("Failed to spawn {:?}", cmd.get_program()))?;
+        return crate::signals::run_foreground(cmd);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_key_line() {
+        let json = r#"{
+  "build": "not this one",
+  "scripts": {
+    "build:watch": "tsc -w",
+    "build": "tsc"
+  }
+}"#;
+        assert_eq!(find_key_line(json, &["scripts", "build"]), Some(5));
+        assert_eq!(find_key_line(json, &["scripts", "build:watch"]), Some(4));
+        assert_eq!(find_key_line(json, &["scripts", "missing"]), None);
+
+        let yaml = "tasks:\n  build:\n    command: cargo build\n";
+        assert_eq!(find_key_line(yaml, &["tasks", "build"]), Some(2));
+
+        let toml = "[tool.poe.tasks]\ntest = \"pytest\"\n";
+        assert_eq!(find_key_line(toml, &["test"]), Some(2));
+    }
+
+    #[test]
+    fn test_arg_completions() {
+        let mut task = Task::new("deploy");
+        task.params = vec![
+            TaskParam {
+                name: "env".to_string(),
+                default: Some("staging".to_string()),
+                variadic: false,
+            },
+            TaskParam {
+                name: "rest".to_string(),
+                default: Some("all".to_string()),
+                variadic: true,
+            },
+        ];
+        task.flags = vec![
+            TaskFlag::new("--force", Some("Skip checks")),
+            TaskFlag::new("REGION=eu", None),
+            TaskFlag::new("REGION=us", None),
+        ];
+
+        let values = |args: &[&str]| -> Vec<String> {
+            return task
+                .arg_completions(args)
+                .into_iter()
+                .map(|(value, _)| value)
+                .collect();
+        };
+
+        assert_eq!(
+            values(&[]),
+            vec!["staging", "--force", "REGION=eu", "REGION=us"]
+        );
+        assert_eq!(
+            values(&["--force"]),
+            vec!["staging", "REGION=eu", "REGION=us"]
+        );
+        assert_eq!(values(&["prod", "REGION=us"]), vec!["all", "--force"]);
+        assert_eq!(
+            values(&["prod", "a", "b"]),
+            vec!["all", "--force", "REGION=eu", "REGION=us"]
+        );
+        assert_eq!(
+            task.arg_completions(&[])[1],
+            ("--force".to_string(), "Skip checks".to_string())
+        );
+    }
+
+    #[test]
+    fn test_find_table_key_line() {
+        let toml = r#"[alias]
+b = "build"
 
-        let exit_status = child
-            .wait()
-            .with_context(|| format!("Failed to wait for command {:?}", cmd.get_program()))?;
+[tool.poe.tasks]
+"lint" = "ruff"
+test.cmd = "pytest"
 
-        return exit_status
-            .code()
-            .ok_or_else(|| anyhow!("Failed to get exit code for {:?}", cmd.get_program()));
+[tool.poe.tasks.serve]
+script = "app:serve"
+"#;
+        assert_eq!(find_table_key_line(toml, "alias", "b"), Some(2));
+        assert_eq!(find_table_key_line(toml, "tool.poe.tasks", "lint"), Some(5));
+        assert_eq!(find_table_key_line(toml, "tool.poe.tasks", "test"), Some(6));
+        assert_eq!(
+            find_table_key_line(toml, "tool.poe.tasks", "serve"),
+            Some(8)
+        );
+        assert_eq!(find_table_key_line(toml, "tool.poe.tasks", "b"), None);
     }
 }
@@ -1,14 +1,127 @@
-use super::runner::Runner;
+use super::runner::{Runner, Task, TaskFlag};
 use anyhow::{Context, Result};
 use std::fs;
+use std::io::Read;
 use std::os::unix::fs::PermissionsExt;
 use std::path::Path;
 use std::process::Command;
 
+// Only the beginning of a script is read when looking for documented flags
+const HEADER_BYTES: u64 = 4096;
+const HEADER_LINES: usize = 50;
+
+/// Flags from an option line like `-v, --verbose  Be verbose` or
+/// `--env=<name>  Target env`. Short flags are used only when there is no
+/// long form.
+fn parse_option_line(text: &str) -> Vec<TaskFlag> {
+    let mut long: Vec<String> = Vec::new();
+    let mut short: Vec<String> = Vec::new();
+    let mut words = text.split_whitespace().peekable();
+
+    while let Some(word) = words.peek() {
+        let word = word.trim_end_matches(',');
+
+        if word.starts_with('-') {
+            let flag = match word.split_once('=') {
+                Some((name, _)) => format!("{}=", name),
+                None => word.to_string(),
+            };
+
+            if flag.starts_with("--") {
+                long.push(flag);
+            } else {
+                short.push(flag);
+            }
+        } else if !word.starts_with('<') && !word.starts_with('[') {
+            break;
+        }
+
+        words.next();
+    }
+
+    let description: Vec<&str> = words.collect();
+    let description = Some(description.join(" ")).filter(|d| !d.is_empty());
+    let flags = if long.is_empty() { short } else { long };
+
+    return flags
+        .into_iter()
+        .map(|flag| TaskFlag::new(flag, description.as_deref()))
+        .collect();
+}
+
+/// Flags from a line like `Usage: deploy.sh [--dry-run] [--env=<env>|-e]`
+fn parse_usage_line(text: &str) -> Vec<TaskFlag> {
+    let mut flags: Vec<TaskFlag> = Vec::new();
+
+    for word in text.split(|c: char| c.is_whitespace() || c == '|' || c == '[' || c == ']') {
+        if !word.starts_with("--") {
+            continue;
+        }
+
+        let flag = match word.split_once('=') {
+            Some((name, _)) => format!("{}=", name),
+            None => word.to_string(),
+        };
+
+        flags.push(TaskFlag::new(flag, None));
+    }
+
+    return flags;
+}
+
+/// Find flags documented in the leading comment block of a script
+fn parse_header_flags(content: &str) -> Vec<TaskFlag> {
+    let mut flags: Vec<TaskFlag> = Vec::new();
+
+    for line in content.lines().take(HEADER_LINES) {
+        let line = line.trim();
+
+        if line.is_empty() || line.starts_with("#!") {
+            continue;
+        }
+
+        let Some(text) = line.strip_prefix('#').or_else(|| line.strip_prefix("//")) else {
+            break;
+        };
+        let text = text.trim();
+
+        let found = if text.starts_with('-') {
+            parse_option_line(text)
+        } else if text.to_lowercase().contains("usage:") {
+            parse_usage_line(text)
+        } else {
+            continue;
+        };
+
+        for flag in found {
+            if !flags.iter().any(|f| f.flag == flag.flag) {
+                flags.push(flag);
+            }
+        }
+    }
+
+    return flags;
+}
+
+fn read_header_flags(path: &Path) -> Vec<TaskFlag> {
+    let mut header = Vec::new();
+    let read =
+        fs::File::open(path).and_then(|file| file.take(HEADER_BYTES).read_to_end(&mut header));
+
+    // Skip binaries
+    if read.is_err() || header.contains(&0) {
+        return Vec::new();
+    }
+
+    return parse_header_flags(&String::from_utf8_lossy(&header));
+}
+
 pub struct ScriptsRunner {
-    tasks: Vec<String>,
+    tasks: Vec<Task>,
     dir: String,
     name: String,
+    /// Every entry of the directory, a script made executable adds a task
+    entries: Vec<String>,
 }
 
 impl ScriptsRunner {
@@ -18,11 +131,14 @@ impl ScriptsRunner {
             dir,
             tasks: Vec::new(),
             name,
+            entries: Vec::new(),
         };
     }
 
-    fn read_scripts(dir: &str) -> Result<Vec<String>> {
-        let mut script_names: Vec<String> = Vec::new();
+    /// Returns the executable scripts and the paths of all entries
+    fn read_scripts(dir: &str) -> Result<(Vec<Task>, Vec<String>)> {
+        let mut script_names: Vec<Task> = Vec::new();
+        let mut entry_paths: Vec<String> = Vec::new();
 
         let dir = Path::new(dir);
 
@@ -30,7 +146,7 @@ impl ScriptsRunner {
             Ok(entries) => entries,
             Err(e) => {
                 if e.kind() == std::io::ErrorKind::NotFound {
-                    return Ok(script_names);
+                    return Ok((script_names, entry_paths));
                 }
 
                 anyhow::bail!(e);
@@ -43,6 +159,8 @@ impl ScriptsRunner {
             };
 
             let path = entry.path();
+            entry_paths.push(path.to_string_lossy().to_string());
+
             let is_executable = path
                 .metadata()
                 .map(|m| m.permissions().mode() & 0o111 != 0)
@@ -56,10 +174,13 @@ impl ScriptsRunner {
                 continue;
             };
 
-            script_names.push(file_name.to_string_lossy().to_string());
+            let mut task = Task::new(file_name.to_string_lossy());
+            task.command = Some(path.to_string_lossy().to_string());
+            task.source = Some(path.clone());
+            script_names.push(task);
         }
 
-        return Ok(script_names);
+        return Ok((script_names, entry_paths));
     }
 }
 
@@ -68,22 +189,124 @@ impl Runner for ScriptsRunner {
         return &self.name;
     }
 
-    fn tasks(&self) -> &Vec<String> {
+    fn marker_files(&self) -> Vec<&str> {
+        return vec![self.dir.as_str()];
+    }
+
+    fn cache_files(&self) -> Vec<&str> {
+        return self.entries.iter().map(String::as_str).collect();
+    }
+
+    fn tasks(&self) -> &Vec<Task> {
         return &self.tasks;
     }
 
+    fn tasks_mut(&mut self) -> &mut Vec<Task> {
+        return &mut self.tasks;
+    }
+
     fn load(&mut self) -> Result<()> {
-        self.tasks = ScriptsRunner::read_scripts(&self.dir)
+        (self.tasks, self.entries) = ScriptsRunner::read_scripts(&self.dir)
             .with_context(|| format!("Failed to read directory {}", self.dir))?;
 
         return Ok(());
     }
 
-    fn run(&self, task: &str, args: &[String]) -> Result<i32> {
-        eprintln!("[rt] Running script {}/{}", self.dir, task);
+    /// The header flags are only read for the script being completed
+    fn arg_completions(&self, task: &str, args: &[&str]) -> Vec<(String, String)> {
+        let Some(task) = self.find_task(task) else {
+            return Vec::new();
+        };
+
+        let mut task = task.clone();
+        if let Some(source) = &task.source {
+            task.flags = read_header_flags(source);
+        }
+
+        return task.arg_completions(args);
+    }
 
+    fn command(&self, task: &str, args: &[String]) -> Result<Command> {
         let fullpath = Path::new(&self.dir).join(task);
         let mut script = Command::new(fullpath);
-        return self.execute(script.args(args));
+        script.args(args);
+        return Ok(script);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn flags(content: &str) -> Vec<(String, Option<String>)> {
+        return parse_header_flags(content)
+            .into_iter()
+            .map(|f| (f.flag, f.description))
+            .collect();
+    }
+
+    #[test]
+    fn test_option_lines() {
+        let content = r#"#!/bin/sh
+# Deploy the app
+#
+# Options:
+#   -n, --dry-run       Only print what would be done
+#   --env=<name>        Target environment
+#   -q                  Quiet
+set -eu
+# --not-in-header  Ignored
+"#;
+
+        assert_eq!(
+            flags(content),
+            vec![
+                (
+                    "--dry-run".to_string(),
+                    Some("Only print what would be done".to_string())
+                ),
+                ("--env=".to_string(), Some("Target environment".to_string())),
+                ("-q".to_string(), Some("Quiet".to_string())),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_usage_line() {
+        let content = "#!/usr/bin/env node\n// Usage: build.js [--watch] [--out=<dir>|--stdout]\n";
+
+        assert_eq!(
+            flags(content),
+            vec![
+                ("--watch".to_string(), None),
+                ("--out=".to_string(), None),
+                ("--stdout".to_string(), None),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_flags_read_when_completing() {
+        let dir = std::env::temp_dir().join(format!("rt-scripts-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let script = dir.join("deploy");
+        fs::write(&script, "#!/bin/sh\n# Usage: deploy [--force]\n").unwrap();
+        fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let mut runner = ScriptsRunner::new(dir.to_string_lossy().to_string());
+        runner.load().unwrap();
+
+        assert!(runner.tasks()[0].flags.is_empty());
+        assert_eq!(
+            runner.arg_completions("deploy", &[]),
+            vec![("--force".to_string(), "option".to_string())]
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_no_header() {
+        assert!(flags("#!/bin/sh\necho --help\n").is_empty());
     }
 }
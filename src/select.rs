@@ -0,0 +1,278 @@
+use crate::runner::Runner;
+
+/// Short names accepted in place of the runner names, eg. `rt npm/build`
+const RUNNER_ALIASES: [(&str, &str); 12] = [
+    ("npm", "package.json"),
+    ("deno", "deno.json"),
+    ("jake", "jakefile"),
+    ("gulp", "gulpfile"),
+    ("grunt", "Gruntfile"),
+    ("composer", "composer.json"),
+    ("moon", "moon.yml"),
+    ("make", "Makefile"),
+    ("just", "justfile"),
+    ("task", "Taskfile.yml"),
+    ("cargo", "Cargo.toml"),
+    ("pyproject", "pyproject.toml"),
+];
+
+/// Does a runner name or alias from the command line or RT_PRIORITY refer to
+/// the runner. `scripts:bin` matches `scripts:./bin`.
+pub fn is_runner_name(runner: &dyn Runner, wanted: &str) -> bool {
+    let name = runner.name();
+
+    if wanted == name {
+        return true;
+    }
+
+    if RUNNER_ALIASES
+        .iter()
+        .any(|(alias, target)| *alias == wanted && *target == name)
+    {
+        return true;
+    }
+
+    return match (
+        name.strip_prefix("scripts:"),
+        wanted.strip_prefix("scripts:"),
+    ) {
+        (Some(dir), Some(wanted_dir)) => {
+            dir.trim_start_matches("./").trim_end_matches('/')
+                == wanted_dir.trim_start_matches("./").trim_end_matches('/')
+        }
+        _ => false,
+    };
+}
+
+/// Runners defining the task with the task name to pass them. A runner can
+/// be picked explicitly with a prefix like `package.json:build` or
+/// `npm/build` when no runner has a task with the full name.
+pub fn find_candidates<'a>(
+    runners: &'a [Box<dyn Runner>],
+    task: &str,
+) -> Vec<(&'a dyn Runner, String)> {
+    let exact: Vec<(&dyn Runner, String)> = runners
+        .iter()
+        .filter(|runner| runner.find_task(task).is_some())
+        .map(|runner| (runner.as_ref(), task.to_string()))
+        .collect();
+
+    if !exact.is_empty() {
+        return exact;
+    }
+
+    for runner in runners {
+        for (index, separator) in task.match_indices([':', '/']) {
+            let prefix = &task[..index];
+            let name = &task[index + separator.len()..];
+
+            if is_runner_name(runner.as_ref(), prefix) && runner.find_task(name).is_some() {
+                return vec![(runner.as_ref(), name.to_string())];
+            }
+        }
+    }
+
+    return Vec::new();
+}
+
+/// Sort candidates by their position in a comma separated RT_PRIORITY list.
+/// Returns true when the first candidate was listed and can be used without
+/// asking.
+pub fn order_by_priority(candidates: &mut [(&dyn Runner, String)], priority: &str) -> bool {
+    let position = |runner: &dyn Runner| {
+        return priority
+            .split(',')
+            .map(|name| name.trim())
+            .position(|name| !name.is_empty() && is_runner_name(runner, name));
+    };
+
+    candidates.sort_by_key(|(runner, _)| position(*runner).unwrap_or(usize::MAX));
+
+    return candidates
+        .first()
+        .is_some_and(|(runner, _)| position(*runner).is_some());
+}
+
+/// Parse an answer to the runner prompt. Empty input picks the first runner
+/// and a trailing `!` asks to remember the choice.
+pub fn parse_choice(input: &str, max: usize) -> Option<(usize, bool)> {
+    let input = input.trim();
+    let (number, remember) = match input.strip_suffix('!') {
+        Some(number) => (number.trim(), true),
+        None => (input, false),
+    };
+
+    if number.is_empty() {
+        return Some((1, remember));
+    }
+
+    return number
+        .parse::<usize>()
+        .ok()
+        .filter(|choice| (1..=max).contains(choice))
+        .map(|choice| (choice, remember));
+}
+
+/// Split arguments like `build test -- --watch` into tasks and the arguments
+/// passed to each of them. Multiple tasks are used only when every word
+/// before `--` is a task. Otherwise the rest are arguments of the first task.
+pub fn split_tasks(args: &[String], is_task: impl Fn(&str) -> bool) -> (Vec<String>, Vec<String>) {
+    let separator = args.iter().position(|arg| arg == "--");
+    let names = &args[..separator.unwrap_or(args.len())];
+
+    if names.len() > 1 && names.iter().all(|name| is_task(name)) {
+        let task_args = match separator {
+            Some(index) => args[index + 1..].to_vec(),
+            None => Vec::new(),
+        };
+        return (names.to_vec(), task_args);
+    }
+
+    return match args.split_first() {
+        Some((first, rest)) => (vec![first.clone()], rest.to_vec()),
+        None => (Vec::new(), Vec::new()),
+    };
+}
+
+/// RT_PRIORITY value with the runner moved to the front
+pub fn prepend_priority(priority: Option<&str>, runner: &str) -> String {
+    let mut names = vec![runner];
+
+    for name in priority.unwrap_or_default().split(',') {
+        let name = name.trim();
+        if !name.is_empty() && !names.contains(&name) {
+            names.push(name);
+        }
+    }
+
+    return names.join(",");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_runner::TestRunner;
+
+    fn runners() -> Vec<Box<dyn Runner>> {
+        return vec![
+            Box::new(TestRunner::new(
+                "package.json".to_string(),
+                vec!["build".to_string(), "db:migrate".to_string()],
+            )),
+            Box::new(TestRunner::new(
+                "Makefile".to_string(),
+                vec!["build".to_string()],
+            )),
+            Box::new(TestRunner::new(
+                "scripts:./bin".to_string(),
+                vec!["deploy".to_string()],
+            )),
+        ];
+    }
+
+    fn names(candidates: &[(&dyn Runner, String)]) -> Vec<(String, String)> {
+        return candidates
+            .iter()
+            .map(|(runner, task)| (runner.name().to_string(), task.clone()))
+            .collect();
+    }
+
+    #[test]
+    fn test_find_candidates() {
+        let runners = runners();
+        let pair = |runner: &str, task: &str| (runner.to_string(), task.to_string());
+
+        assert_eq!(
+            names(&find_candidates(&runners, "build")),
+            vec![pair("package.json", "build"), pair("Makefile", "build")]
+        );
+        assert_eq!(
+            names(&find_candidates(&runners, "npm/build")),
+            vec![pair("package.json", "build")]
+        );
+        assert_eq!(
+            names(&find_candidates(&runners, "make:build")),
+            vec![pair("Makefile", "build")]
+        );
+        assert_eq!(
+            names(&find_candidates(&runners, "package.json:db:migrate")),
+            vec![pair("package.json", "db:migrate")]
+        );
+        assert_eq!(
+            names(&find_candidates(&runners, "db:migrate")),
+            vec![pair("package.json", "db:migrate")]
+        );
+        assert_eq!(
+            names(&find_candidates(&runners, "scripts:bin/deploy")),
+            vec![pair("scripts:./bin", "deploy")]
+        );
+        assert!(find_candidates(&runners, "make/deploy").is_empty());
+        assert!(find_candidates(&runners, "missing").is_empty());
+    }
+
+    #[test]
+    fn test_order_by_priority() {
+        let runners = runners();
+
+        let mut candidates = find_candidates(&runners, "build");
+        assert!(order_by_priority(&mut candidates, "just,make"));
+        assert_eq!(candidates[0].0.name(), "Makefile");
+
+        let mut candidates = find_candidates(&runners, "build");
+        assert!(!order_by_priority(&mut candidates, ""));
+        assert_eq!(candidates[0].0.name(), "package.json");
+    }
+
+    #[test]
+    fn test_parse_choice() {
+        assert_eq!(parse_choice("2\n", 12), Some((2, false)));
+        assert_eq!(parse_choice("11", 12), Some((11, false)));
+        assert_eq!(parse_choice("3!", 12), Some((3, true)));
+        assert_eq!(parse_choice("", 12), Some((1, false)));
+        assert_eq!(parse_choice("0", 12), None);
+        assert_eq!(parse_choice("13", 12), None);
+        assert_eq!(parse_choice("x", 12), None);
+    }
+
+    #[test]
+    fn test_split_tasks() {
+        let split = |args: &str| {
+            let args: Vec<String> = args.split_whitespace().map(|a| a.to_string()).collect();
+            return split_tasks(&args, |name| ["build", "test", "lint"].contains(&name));
+        };
+        let strings = |items: &[&str]| -> Vec<String> {
+            return items.iter().map(|item| item.to_string()).collect();
+        };
+
+        assert_eq!(
+            split("build test lint"),
+            (strings(&["build", "test", "lint"]), vec![])
+        );
+        assert_eq!(
+            split("build test -- --watch"),
+            (strings(&["build", "test"]), strings(&["--watch"]))
+        );
+        // A single task gets all the arguments as before
+        assert_eq!(
+            split("test -- --watch"),
+            (strings(&["test"]), strings(&["--", "--watch"]))
+        );
+        assert_eq!(
+            split("test --watch lint"),
+            (strings(&["test"]), strings(&["--watch", "lint"]))
+        );
+        assert_eq!(
+            split("build prod"),
+            (strings(&["build"]), strings(&["prod"]))
+        );
+    }
+
+    #[test]
+    fn test_prepend_priority() {
+        assert_eq!(prepend_priority(None, "Makefile"), "Makefile");
+        assert_eq!(
+            prepend_priority(Some("package.json, Makefile"), "Makefile"),
+            "Makefile,package.json"
+        );
+    }
+}
@@ -0,0 +1,176 @@
+use anyhow::{bail, Result};
+
+// Templates use {name} for the completed command and {rt} for the rt
+// invocation including any --runners-env flag
+
+const ZSH_COMPLETION: &str = r#"compdef 'eval "$({rt} --zsh-complete $LBUFFER $RBUFFER)"' {name}
+"#;
+
+const ZSH_WRAPPER: &str = r#"{name}() {
+    {rt} "$@"
+}
+"#;
+
+/// Bash exports COMP_LINE and COMP_POINT to `complete -C` commands and
+/// `-o default` falls back to file completion when rt prints nothing
+const BASH_COMPLETION: &str = r#"complete -o default -C '{rt} --bash-complete' {name}
+"#;
+
+const BASH_WRAPPER: &str = ZSH_WRAPPER;
+
+/// The whole buffer up to the cursor is passed so chained commands are
+/// handled by rt. Paths are completed when rt prints nothing.
+const FISH_COMPLETION: &str = r#"function __{name}_complete
+    set -l items ({rt} --fish-complete (commandline -c | string collect))
+    if test (count $items) -eq 0
+        __fish_complete_path (commandline -ct)
+    else
+        printf '%s\n' $items
+    end
+end
+complete -c {name} -f -a '(__{name}_complete)'
+"#;
+
+const FISH_WRAPPER: &str = r#"function {name} --wraps rt
+    {rt} $argv
+end
+"#;
+
+/// Returning null from the external completer makes nu fall back to its own
+/// file completion. Other commands go to the completer set before, eg.
+/// carapace.
+const NU_COMPLETION: &str = r#"let rt_previous_completer = $env.config.completions.external.completer?
+$env.config.completions.external.enable = true
+$env.config.completions.external.completer = {|spans|
+    if $spans.0 == "{name}" {
+        {rt} --nu-complete ($spans | str join ' ') | from json
+    } else if $rt_previous_completer != null {
+        do $rt_previous_completer $spans
+    } else {
+        null
+    }
+}
+"#;
+
+const NU_WRAPPER: &str = r#"def --wrapped {name} [...args] {
+    {rt} ...$args
+}
+"#;
+
+/// The command text is cut or padded to the cursor so a trailing space is
+/// kept. rt prints CompletionResult objects which are evaluated here.
+const POWERSHELL_COMPLETION: &str = r#"Register-ArgumentCompleter -Native -CommandName {name} -ScriptBlock {
+    param($wordToComplete, $commandAst, $cursorPosition)
+    $line = $commandAst.Extent.Text
+    $offset = $cursorPosition - $commandAst.Extent.StartOffset
+    if ($offset -lt $line.Length) {
+        $line = $line.Substring(0, $offset)
+    } else {
+        $line = $line.PadRight($offset)
+    }
+    # rt prints value<TAB>tooltip lines which are only used as data
+    {rt} --pwsh-complete "$line" | ForEach-Object {
+        $value, $tooltip = $_ -split "`t", 2
+        [System.Management.Automation.CompletionResult]::new($value, $value, 'ParameterValue', $tooltip)
+    }
+}
+"#;
+
+const POWERSHELL_WRAPPER: &str = r#"function {name} {
+    {rt} @args
+}
+"#;
+
+/// Shell integration snippet for `rt --init <shell> [name]`. A wrapper
+/// function is included when completing a command other than rt, eg. an
+/// `rtn` alias reading its runners from another environment variable.
+pub fn get_init_script(
+    shell: &str,
+    name: Option<&str>,
+    runners_env: Option<&str>,
+) -> Result<String> {
+    let (completion, wrapper) = match shell {
+        "zsh" => (ZSH_COMPLETION, ZSH_WRAPPER),
+        "bash" => (BASH_COMPLETION, BASH_WRAPPER),
+        "fish" => (FISH_COMPLETION, FISH_WRAPPER),
+        "nu" => (NU_COMPLETION, NU_WRAPPER),
+        "powershell" | "pwsh" => (POWERSHELL_COMPLETION, POWERSHELL_WRAPPER),
+        _ => bail!(
+            "Unsupported shell '{}'. Use one of zsh, bash, fish, nu or powershell",
+            shell
+        ),
+    };
+
+    let name = name.unwrap_or("rt");
+    let rt = match runners_env {
+        Some(env_name) => format!("rt --runners-env {}", env_name),
+        None => "rt".to_string(),
+    };
+
+    if name == "rt" && runners_env.is_some() {
+        bail!(
+            "A wrapper name is required with --runners-env, eg. rt --runners-env RTN_RUNNERS --init {} rtn",
+            shell
+        );
+    }
+
+    let mut script = String::new();
+    if name != "rt" {
+        script.push_str(wrapper);
+    }
+    script.push_str(completion);
+
+    return Ok(script.replace("{name}", name).replace("{rt}", &rt));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_scripts() {
+        assert_eq!(
+            get_init_script("zsh", None, None).unwrap(),
+            "compdef 'eval \"$(rt --zsh-complete $LBUFFER $RBUFFER)\"' rt\n"
+        );
+        assert_eq!(
+            get_init_script("bash", None, None).unwrap(),
+            "complete -o default -C 'rt --bash-complete' rt\n"
+        );
+
+        let fish = get_init_script("fish", None, None).unwrap();
+        assert!(fish.contains("complete -c rt -f -a '(__rt_complete)'"));
+        assert!(!fish.contains("--wraps"));
+    }
+
+    #[test]
+    fn test_wrapper_for_runners_env() {
+        let zsh = get_init_script("zsh", Some("rtn"), Some("RTN_RUNNERS")).unwrap();
+        assert_eq!(
+            zsh,
+            r#"rtn() {
+    rt --runners-env RTN_RUNNERS "$@"
+}
+compdef 'eval "$(rt --runners-env RTN_RUNNERS --zsh-complete $LBUFFER $RBUFFER)"' rtn
+"#
+        );
+
+        let nu = get_init_script("nu", Some("rtn"), Some("RTN_RUNNERS")).unwrap();
+        assert!(nu.starts_with(
+            "def --wrapped rtn [...args] {\n    rt --runners-env RTN_RUNNERS ...$args\n}"
+        ));
+        assert!(nu.contains(r#"if $spans.0 == "rtn" {"#));
+        assert!(nu.contains("do $rt_previous_completer $spans"));
+
+        let powershell = get_init_script("pwsh", Some("rtn"), Some("RTN_RUNNERS")).unwrap();
+        assert!(powershell.contains("-CommandName rtn"));
+        assert!(!powershell.contains("Invoke-Expression"));
+        assert!(powershell.contains("rt --runners-env RTN_RUNNERS --pwsh-complete"));
+    }
+
+    #[test]
+    fn test_errors() {
+        assert!(get_init_script("tcsh", None, None).is_err());
+        assert!(get_init_script("zsh", None, Some("RTN_RUNNERS")).is_err());
+    }
+}
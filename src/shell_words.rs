@@ -0,0 +1,279 @@
+#[derive(Debug, PartialEq)]
+enum Token {
+    Word(String),
+    /// Command separator like `&&`, `|`, `;` or the start of a subshell
+    Separator,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Quote {
+    None,
+    Single,
+    Double,
+}
+
+/// Split a command line into words and separators the way a POSIX shell
+/// would. The last token is always the word under the cursor which is empty
+/// when the input ends with whitespace.
+fn tokenize(input: &str) -> Vec<Token> {
+    let mut tokens: Vec<Token> = Vec::new();
+    let mut word = String::new();
+    let mut quote = Quote::None;
+    let mut in_word = false;
+    let mut chars = input.chars().peekable();
+
+    while let Some(a_char) = chars.next() {
+        match (quote, a_char) {
+            (Quote::Single, '\'') => quote = Quote::None,
+            (Quote::Single, _) => word.push(a_char),
+            (Quote::Double, '"') => quote = Quote::None,
+            (_, '$') if chars.peek() == Some(&'(') => {
+                // Command substitution starts a new command even inside
+                // double quotes
+                chars.next();
+                tokens.push(Token::Separator);
+                word.clear();
+                in_word = false;
+                quote = Quote::None;
+            }
+            (Quote::Double, '\\') => match chars.peek() {
+                Some('$' | '`' | '"' | '\\' | '\n') => word.push(chars.next().unwrap_or('\\')),
+                _ => word.push(a_char),
+            },
+            (Quote::Double, _) => word.push(a_char),
+            (Quote::None, '\\') => {
+                in_word = true;
+                if let Some(escaped) = chars.next() {
+                    word.push(escaped);
+                }
+            }
+            (Quote::None, '\'') => {
+                in_word = true;
+                quote = Quote::Single;
+            }
+            (Quote::None, '"') => {
+                in_word = true;
+                quote = Quote::Double;
+            }
+            (Quote::None, ';' | '&' | '|' | '(' | ')' | '`' | '\n') => {
+                if in_word {
+                    tokens.push(Token::Word(std::mem::take(&mut word)));
+                    in_word = false;
+                }
+                if tokens.last() != Some(&Token::Separator) {
+                    tokens.push(Token::Separator);
+                }
+            }
+            (Quote::None, _) if a_char.is_whitespace() => {
+                if in_word {
+                    tokens.push(Token::Word(std::mem::take(&mut word)));
+                    in_word = false;
+                }
+            }
+            (Quote::None, _) => {
+                in_word = true;
+                word.push(a_char);
+            }
+        }
+    }
+
+    tokens.push(Token::Word(word));
+    return tokens;
+}
+
+fn is_assignment(word: &str) -> bool {
+    let Some((name, _)) = word.split_once('=') else {
+        return false;
+    };
+
+    return !name.is_empty()
+        && !name.starts_with(|c: char| c.is_ascii_digit())
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+}
+
+/// Commands which run the following words as a command. Options listed with
+/// them take a value.
+const PREFIX_COMMANDS: [(&str, &[&str]); 7] = [
+    (
+        "sudo",
+        &["-u", "-g", "-h", "-p", "-C", "-D", "-r", "-t", "-U"],
+    ),
+    ("env", &["-u", "-C", "-S"]),
+    ("nice", &["-n"]),
+    ("command", &[]),
+    ("exec", &["-a"]),
+    ("nohup", &[]),
+    ("time", &[]),
+];
+
+/// Drop environment assignments and wrappers like `sudo` or `env X=1` so the
+/// first word is the command itself
+fn skip_command_prefix(words: &[String]) -> &[String] {
+    let mut index = 0;
+
+    // Never skip the word under the cursor
+    while index + 1 < words.len() {
+        let word = words[index].as_str();
+
+        if is_assignment(word) {
+            index += 1;
+            continue;
+        }
+
+        let Some((_, value_options)) = PREFIX_COMMANDS.iter().find(|(name, _)| *name == word)
+        else {
+            break;
+        };
+
+        index += 1;
+        while index + 1 < words.len() {
+            let option = words[index].as_str();
+
+            if value_options.contains(&option) {
+                index += 2;
+            } else if option.starts_with('-') || (word == "env" && is_assignment(option)) {
+                index += 1;
+            } else {
+                break;
+            }
+        }
+    }
+
+    return &words[index.min(words.len() - 1)..];
+}
+
+/// Words of the command being completed up to the cursor. The first word is
+/// the command name, eg. rt or an alias of it, and the last is the partial
+/// word under the cursor.
+pub fn command_words(lbuffer: &str) -> Vec<String> {
+    let tokens = tokenize(lbuffer);
+
+    let start = tokens
+        .iter()
+        .rposition(|token| *token == Token::Separator)
+        .map(|index| index + 1)
+        .unwrap_or(0);
+
+    let words: Vec<String> = tokens
+        .into_iter()
+        .skip(start)
+        .filter_map(|token| match token {
+            Token::Word(word) => Some(word),
+            Token::Separator => None,
+        })
+        .collect();
+
+    return skip_command_prefix(&words).to_vec();
+}
+
+/// Complete words after the cursor until the end of the command. The rest of
+/// the word under the cursor is not included.
+pub fn words_after_cursor(rbuffer: &str) -> Vec<String> {
+    let mut words: Vec<String> = Vec::new();
+    let tokens = tokenize(rbuffer);
+    let continues_word = !rbuffer.starts_with(char::is_whitespace);
+
+    for (index, token) in tokens.into_iter().enumerate() {
+        match token {
+            Token::Separator => break,
+            Token::Word(_) if index == 0 && continues_word => {}
+            Token::Word(word) if word.is_empty() => {}
+            Token::Word(word) => words.push(word),
+        }
+    }
+
+    return words;
+}
+
+/// Quote a word for a POSIX shell when needed
+pub fn quote(word: &str) -> String {
+    let is_safe = !word.is_empty()
+        && word
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./:=@%+,".contains(c));
+
+    if is_safe {
+        return word.to_string();
+    }
+
+    return format!("'{}'", word.replace('\'', r"'\''"));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(lbuffer: &str) -> Vec<String> {
+        return command_words(lbuffer);
+    }
+
+    #[test]
+    fn test_simple_words() {
+        assert_eq!(words("rt"), vec!["rt"]);
+        assert_eq!(words("rt "), vec!["rt", ""]);
+        assert_eq!(words("rt  build   arg"), vec!["rt", "build", "arg"]);
+        assert_eq!(words(""), vec![""]);
+    }
+
+    #[test]
+    fn test_quotes_and_escapes() {
+        assert_eq!(
+            words(r#"rt build "a b" 'c d' e\ f "#),
+            vec!["rt", "build", "a b", "c d", "e f", ""]
+        );
+        assert_eq!(
+            words(r#"rt build "x\"y" 'a;b' "#),
+            vec!["rt", "build", "x\"y", "a;b", ""]
+        );
+        assert_eq!(
+            words(r#"rt build "unfinished"#),
+            vec!["rt", "build", "unfinished"]
+        );
+        assert_eq!(words(r#"rt build "" "#), vec!["rt", "build", "", ""]);
+    }
+
+    #[test]
+    fn test_separators() {
+        assert_eq!(words("ls && rt fo"), vec!["rt", "fo"]);
+        assert_eq!(words("false || rt fo"), vec!["rt", "fo"]);
+        assert_eq!(words("cat x | rt fo"), vec!["rt", "fo"]);
+        assert_eq!(words("ls;rt fo"), vec!["rt", "fo"]);
+        assert_eq!(words("(cd app && rt fo"), vec!["rt", "fo"]);
+        assert_eq!(words(r#"echo "$(rt fo"#), vec!["rt", "fo"]);
+        assert_eq!(words("echo `rt fo"), vec!["rt", "fo"]);
+        assert_eq!(
+            words("rt build 'a && b' "),
+            vec!["rt", "build", "a && b", ""]
+        );
+    }
+
+    #[test]
+    fn test_command_prefixes() {
+        assert_eq!(words("sudo rt fo"), vec!["rt", "fo"]);
+        assert_eq!(words("sudo -E -u deploy rt fo"), vec!["rt", "fo"]);
+        assert_eq!(words("env X=1 Y=2 rt fo"), vec!["rt", "fo"]);
+        assert_eq!(words("env -u HOME rt fo"), vec!["rt", "fo"]);
+        assert_eq!(words("NODE_ENV=test rt fo"), vec!["rt", "fo"]);
+        assert_eq!(words("nice -n 10 time rt fo"), vec!["rt", "fo"]);
+        assert_eq!(words("rt build X=1"), vec!["rt", "build", "X=1"]);
+        assert_eq!(words("sudo"), vec!["sudo"]);
+        assert_eq!(words("sudo "), vec![""]);
+    }
+
+    #[test]
+    fn test_quote() {
+        assert_eq!(quote("build"), "build");
+        assert_eq!(quote("--out=dist/app.js"), "--out=dist/app.js");
+        assert_eq!(quote("a b"), "'a b'");
+        assert_eq!(quote(""), "''");
+        assert_eq!(quote("it's"), r"'it'\''s'");
+    }
+
+    #[test]
+    fn test_words_after_cursor() {
+        assert_eq!(words_after_cursor(""), Vec::<String>::new());
+        assert_eq!(words_after_cursor("ld --watch"), vec!["--watch"]);
+        assert_eq!(words_after_cursor(" --watch 'a b'"), vec!["--watch", "a b"]);
+        assert_eq!(words_after_cursor(" --watch && rt --fix"), vec!["--watch"]);
+    }
+}
@@ -0,0 +1,270 @@
+use crate::parallel::exit_code;
+use anyhow::{Context, Result};
+use std::io::{self, IsTerminal};
+use std::os::unix::process::{CommandExt, ExitStatusExt};
+use std::process::{Command, ExitStatus};
+use std::sync::atomic::{AtomicI32, Ordering};
+
+/// Signals passed on to the running task instead of stopping rt
+const FORWARDED: [libc::c_int; 3] = [libc::SIGINT, libc::SIGTERM, libc::SIGHUP];
+
+/// Last signal received, 0 when none
+static RECEIVED: AtomicI32 = AtomicI32::new(0);
+/// Signal received since the last `take_pending`, 0 when none
+static PENDING: AtomicI32 = AtomicI32::new(0);
+/// Process group the signals are forwarded to, 0 when none
+static FORWARD_GROUP: AtomicI32 = AtomicI32::new(0);
+
+/// Held by tests changing the dispositions as they are global
+#[cfg(test)]
+pub static TEST_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+extern "C" fn on_signal(signal: libc::c_int) {
+    RECEIVED.store(signal, Ordering::SeqCst);
+    PENDING.store(signal, Ordering::SeqCst);
+
+    let group = FORWARD_GROUP.load(Ordering::SeqCst);
+    if group != 0 {
+        unsafe {
+            libc::kill(-group, signal);
+        }
+    }
+}
+
+/// Dispositions of the forwarded signals replaced by `listen`
+pub struct Dispositions([libc::sighandler_t; FORWARDED.len()]);
+
+/// Catch SIGINT, SIGTERM and SIGHUP instead of exiting. Returns the previous
+/// dispositions for `restore`.
+pub fn listen() -> Dispositions {
+    PENDING.store(0, Ordering::SeqCst);
+    let mut previous = [libc::SIG_DFL; FORWARDED.len()];
+
+    for (index, signal) in FORWARDED.iter().enumerate() {
+        previous[index] = unsafe { libc::signal(*signal, on_signal as libc::sighandler_t) };
+    }
+
+    return Dispositions(previous);
+}
+
+/// Put back the dispositions from before `listen`
+pub fn restore(dispositions: Dispositions) {
+    for (signal, handler) in FORWARDED.iter().zip(dispositions.0.iter()) {
+        unsafe {
+            libc::signal(*signal, *handler);
+        }
+    }
+}
+
+/// Change the mask of the forwarded signals, returns the previous mask
+fn mask_forwarded(how: libc::c_int) -> libc::sigset_t {
+    unsafe {
+        let mut signals: libc::sigset_t = std::mem::zeroed();
+        let mut previous: libc::sigset_t = std::mem::zeroed();
+
+        libc::sigemptyset(&mut signals);
+        for signal in FORWARDED {
+            libc::sigaddset(&mut signals, signal);
+        }
+        libc::pthread_sigmask(how, &signals, &mut previous);
+
+        return previous;
+    }
+}
+
+/// The last signal caught after `listen`
+pub fn received() -> Option<i32> {
+    let signal = RECEIVED.load(Ordering::SeqCst);
+    return (signal != 0).then_some(signal);
+}
+
+/// The signal caught since the last call, for forwarding it by hand
+pub fn take_pending() -> Option<i32> {
+    let signal = PENDING.swap(0, Ordering::SeqCst);
+    return (signal != 0).then_some(signal);
+}
+
+/// Send the signal to every process in the group
+pub fn kill_group(group: libc::pid_t, signal: i32) {
+    unsafe {
+        libc::kill(-group, signal);
+    }
+}
+
+/// Is rt in the foreground process group of the terminal on stdin
+pub fn owns_terminal() -> bool {
+    return io::stdin().is_terminal()
+        && unsafe { libc::tcgetpgrp(libc::STDIN_FILENO) == libc::getpgrp() };
+}
+
+/// Make the process group the foreground group of the terminal. SIGTTOU is
+/// ignored meanwhile as a background group is stopped when doing it.
+/// Only uses async-signal-safe calls so it works between fork and exec.
+pub fn set_foreground(group: libc::pid_t) {
+    unsafe {
+        let previous = libc::signal(libc::SIGTTOU, libc::SIG_IGN);
+        libc::tcsetpgrp(libc::STDIN_FILENO, group);
+        libc::signal(libc::SIGTTOU, previous);
+    }
+}
+
+/// Give the terminal back to rt
+pub fn take_terminal() {
+    set_foreground(unsafe { libc::getpgrp() });
+}
+
+/// Make the command the foreground group of the terminal when it starts.
+/// Also done in the child so it never reads the terminal before the parent
+/// has handed it over.
+pub fn foreground_on_exec(command: &mut Command) {
+    unsafe {
+        command.pre_exec(|| {
+            set_foreground(libc::getpid());
+            return Ok(());
+        });
+    }
+}
+
+/// Wait for the task. When it is suspended with Ctrl-Z rt suspends itself too
+/// and continues the task when it is resumed.
+fn wait(pid: libc::pid_t, terminal: bool) -> Result<ExitStatus> {
+    loop {
+        let mut status: libc::c_int = 0;
+        let flags = if terminal { libc::WUNTRACED } else { 0 };
+
+        if unsafe { libc::waitpid(pid, &mut status, flags) } < 0 {
+            let error = io::Error::last_os_error();
+            if error.kind() == io::ErrorKind::Interrupted {
+                continue;
+            }
+            return Err(error).context("Failed to wait for the task");
+        }
+
+        if !libc::WIFSTOPPED(status) {
+            return Ok(ExitStatus::from_raw(status));
+        }
+
+        take_terminal();
+        unsafe {
+            libc::kill(libc::getpid(), libc::SIGSTOP);
+        }
+
+        // Resumed with `fg` or `bg`
+        if owns_terminal() {
+            set_foreground(pid);
+        }
+        unsafe {
+            libc::kill(-pid, libc::SIGCONT);
+        }
+    }
+}
+
+/// Run the command in its own process group which gets the terminal. SIGINT,
+/// SIGTERM and SIGHUP sent to rt are forwarded to the group. Returns the exit
+/// code, 128 + the signal number when the task was killed by a signal.
+pub fn run_foreground(command: &mut Command) -> Result<i32> {
+    let terminal = owns_terminal();
+
+    // The forwarded signals are blocked until the process group to forward
+    // them to is known. Signals arriving meanwhile are delivered right after.
+    let dispositions = listen();
+    let mask = mask_forwarded(libc::SIG_BLOCK);
+
+    command.process_group(0);
+    unsafe {
+        command.pre_exec(move || {
+            libc::pthread_sigmask(libc::SIG_SETMASK, &mask, std::ptr::null_mut());
+            return Ok(());
+        });
+    }
+    if terminal {
+        foreground_on_exec(command);
+    }
+
+    let child = command.spawn();
+    if let Ok(child) = &child {
+        FORWARD_GROUP.store(child.id() as libc::pid_t, Ordering::SeqCst);
+    }
+    unsafe {
+        libc::pthread_sigmask(libc::SIG_SETMASK, &mask, std::ptr::null_mut());
+    }
+
+    let child = match child {
+        Ok(child) => child,
+        Err(error) => {
+            restore(dispositions);
+            return Err(error)
+                .with_context(|| format!("Failed to spawn {:?}", command.get_program()));
+        }
+    };
+
+    let pid = child.id() as libc::pid_t;
+    if terminal {
+        set_foreground(pid);
+    }
+
+    let status = wait(pid, terminal);
+
+    FORWARD_GROUP.store(0, Ordering::SeqCst);
+    if terminal {
+        take_terminal();
+    }
+    restore(dispositions);
+
+    return Ok(exit_code(status?));
+}
+
+/// Replace rt with the command. Only returns on errors.
+pub fn exec(command: &mut Command) -> anyhow::Error {
+    let error = command.exec();
+    return anyhow::Error::new(error)
+        .context(format!("Failed to execute {:?}", command.get_program()));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shell(script: &str) -> Command {
+        let mut command = Command::new("sh");
+        command.arg("-c").arg(script);
+        return command;
+    }
+
+    // One test as the forwarding state is global
+    #[test]
+    fn test_run_foreground() {
+        let _lock = TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+        assert_eq!(run_foreground(&mut shell("exit 0")).unwrap(), 0);
+        assert_eq!(run_foreground(&mut shell("exit 3")).unwrap(), 3);
+        assert_eq!(run_foreground(&mut shell("kill -TERM $$")).unwrap(), 143);
+
+        let sender = std::thread::spawn(|| {
+            std::thread::sleep(std::time::Duration::from_millis(300));
+            on_signal(libc::SIGINT);
+        });
+        assert_eq!(run_foreground(&mut shell("sleep 5")).unwrap(), 130);
+        sender.join().unwrap();
+        assert_eq!(received(), Some(libc::SIGINT));
+
+        // The default dispositions are back
+        for signal in FORWARDED {
+            let handler = unsafe { libc::signal(signal, libc::SIG_DFL) };
+            assert_eq!(handler, libc::SIG_DFL);
+        }
+
+        // Signals blocked while spawning are delivered afterwards
+        let dispositions = listen();
+        let mask = mask_forwarded(libc::SIG_BLOCK);
+        unsafe {
+            libc::raise(libc::SIGHUP);
+        }
+        assert_eq!(received(), Some(libc::SIGINT));
+        unsafe {
+            libc::pthread_sigmask(libc::SIG_SETMASK, &mask, std::ptr::null_mut());
+        }
+        assert_eq!(received(), Some(libc::SIGHUP));
+        restore(dispositions);
+    }
+}
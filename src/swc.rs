@@ -0,0 +1,250 @@
+use anyhow::{anyhow, bail, Context, Result};
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::io::ErrorKind;
+use std::path::Path;
+use swc_common::{sync::Lrc, SourceMap};
+use swc_ecma_ast::{
+    BlockStmtOrExpr, Callee, Decl, Expr, ExprOrSpread, ImportSpecifier, Lit, MemberProp, Module,
+    ModuleDecl, ModuleExportName, ModuleItem, ObjectPatProp, Pat, PropName, Stmt,
+};
+use swc_ecma_parser::{lexer::Lexer, Parser, StringInput, Syntax};
+
+/// TypeScript syntax for `.ts` files, otherwise ecmascript
+pub fn syntax_for(path: &str) -> Syntax {
+    if path.ends_with(".ts") {
+        return Syntax::Typescript(Default::default());
+    }
+    return Syntax::Es(Default::default());
+}
+
+pub fn parse_as_swc_module(path: &str) -> Result<Option<(Lrc<SourceMap>, Module)>> {
+    let cm: Lrc<SourceMap> = Default::default();
+
+    let maybe_file = cm.load_file(Path::new(path));
+
+    let fm = match maybe_file {
+        Ok(f) => f,
+        Err(e) => {
+            if ErrorKind::NotFound == e.kind() {
+                return Ok(None);
+            }
+            bail!("Failed to read {}: {}", path, e.to_string());
+        }
+    };
+
+    let lexer = Lexer::new(
+        syntax_for(path),
+        // EsVersion defaults to es5
+        Default::default(),
+        StringInput::from(&*fm),
+        None,
+    );
+
+    let mut parser = Parser::new_from(lexer);
+
+    return match parser.parse_module() {
+        Ok(m) => Ok(Some((cm, m))),
+        Err(e) => {
+            return Err(anyhow!("Parse error {:?}", e))
+                .context(format!("Failed to parse {}", path));
+        }
+    };
+}
+
+/// String literal argument of a call
+pub fn string_arg(args: &[ExprOrSpread], index: usize) -> Option<String> {
+    let Expr::Lit(Lit::Str(string_literal)) = args.get(index)?.expr.borrow() else {
+        return None;
+    };
+    return Some(string_literal.value.to_string());
+}
+
+/// The module name from `require("name")`
+pub fn required_module(expr: &Expr) -> Option<String> {
+    let Expr::Call(call) = expr else {
+        return None;
+    };
+    let Callee::Expr(callee) = &call.callee else {
+        return None;
+    };
+    if !matches!(callee.borrow(), Expr::Ident(ident) if ident.sym == "require") {
+        return None;
+    }
+    return string_arg(&call.args, 0);
+}
+
+/// Object and property names of a method call like `gulp.task()`
+pub fn member_call(callee: &Callee) -> Option<(&str, &str)> {
+    let Callee::Expr(expr) = callee else {
+        return None;
+    };
+    let Expr::Member(member) = expr.borrow() else {
+        return None;
+    };
+    let Expr::Ident(object) = member.obj.borrow() else {
+        return None;
+    };
+    let MemberProp::Ident(property) = &member.prop else {
+        return None;
+    };
+    return Some((&object.sym, &property.sym));
+}
+
+/// Statements of a function or an arrow function with a block body
+pub fn function_body(expr: &Expr) -> Option<&[Stmt]> {
+    return match expr {
+        Expr::Fn(function) => function
+            .function
+            .body
+            .as_ref()
+            .map(|body| body.stmts.as_slice()),
+        Expr::Arrow(arrow) => match arrow.body.borrow() {
+            BlockStmtOrExpr::BlockStmt(body) => Some(&body.stmts),
+            BlockStmtOrExpr::Expr(_) => None,
+        },
+        _ => None,
+    };
+}
+
+/// Local names bound to the functions and the object of a module by imports
+/// and requires like `import { task as jakeTask } from "jake"` or
+/// `const gulp = require("gulp")`
+#[derive(Debug, Default, PartialEq)]
+pub struct ModuleBindings {
+    /// Local name to the function name in the module
+    functions: HashMap<String, String>,
+    /// Names of the module object, eg. `jake` from `import * as jake`
+    modules: Vec<String>,
+    /// Does the module define its functions and an object with its name as
+    /// globals like jake does
+    globals: bool,
+}
+
+impl ModuleBindings {
+    pub fn from_module(module: &Module, source: &str, globals: bool) -> Self {
+        let mut bindings = ModuleBindings {
+            functions: HashMap::new(),
+            modules: Vec::new(),
+            globals,
+        };
+
+        if globals {
+            bindings.modules.push(source.to_string());
+        }
+
+        for item in module.body.iter() {
+            match item {
+                ModuleItem::ModuleDecl(ModuleDecl::Import(import))
+                    if import.src.value == source =>
+                {
+                    for specifier in &import.specifiers {
+                        bindings.add_import(specifier);
+                    }
+                }
+                ModuleItem::Stmt(Stmt::Decl(Decl::Var(var))) => {
+                    for declarator in &var.decls {
+                        let is_required = declarator
+                            .init
+                            .as_deref()
+                            .and_then(required_module)
+                            .is_some_and(|module| module == source);
+
+                        if is_required {
+                            bindings.add_pattern(&declarator.name);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        return bindings;
+    }
+
+    fn add_import(&mut self, specifier: &ImportSpecifier) {
+        match specifier {
+            ImportSpecifier::Named(named) => {
+                let imported = match &named.imported {
+                    Some(ModuleExportName::Ident(ident)) => ident.sym.to_string(),
+                    Some(ModuleExportName::Str(string)) => string.value.to_string(),
+                    None => named.local.sym.to_string(),
+                };
+                self.functions.insert(named.local.sym.to_string(), imported);
+            }
+            ImportSpecifier::Default(default) => self.modules.push(default.local.sym.to_string()),
+            ImportSpecifier::Namespace(namespace) => {
+                self.modules.push(namespace.local.sym.to_string())
+            }
+        }
+    }
+
+    fn add_pattern(&mut self, pat: &Pat) {
+        match pat {
+            Pat::Ident(ident) => self.modules.push(ident.id.sym.to_string()),
+            Pat::Object(object) => {
+                for prop in &object.props {
+                    match prop {
+                        ObjectPatProp::Assign(assign) => {
+                            let name = assign.key.id.sym.to_string();
+                            self.functions.insert(name.clone(), name);
+                        }
+                        ObjectPatProp::KeyValue(key_value) => {
+                            let (PropName::Ident(key), Pat::Ident(local)) =
+                                (&key_value.key, key_value.value.borrow())
+                            else {
+                                continue;
+                            };
+                            self.functions
+                                .insert(local.id.sym.to_string(), key.sym.to_string());
+                        }
+                        ObjectPatProp::Rest(_) => {}
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Name of the called module function, eg. "task" for `jake.task()` or
+    /// `jakeTask()` when imported with that name
+    pub fn callee_name<'a>(&'a self, callee: &'a Callee) -> Option<&'a str> {
+        let Callee::Expr(expr) = callee else {
+            return None;
+        };
+
+        return match expr.borrow() {
+            Expr::Ident(ident) => match self.functions.get(&*ident.sym) {
+                Some(name) => Some(name),
+                None => self.globals.then_some(&*ident.sym),
+            },
+            _ => {
+                let (object, property) = member_call(callee)?;
+                self.modules
+                    .iter()
+                    .any(|module| module == object)
+                    .then_some(property)
+            }
+        };
+    }
+}
+
+/// Parse source code for tests
+#[cfg(test)]
+pub fn parse_code(path: &str, code: &str) -> (Lrc<SourceMap>, Module) {
+    let cm: Lrc<SourceMap> = Default::default();
+    let fm = cm.new_source_file(
+        std::rc::Rc::new(swc_common::FileName::Custom(path.into())),
+        code.into(),
+    );
+    let lexer = Lexer::new(
+        syntax_for(path),
+        Default::default(),
+        StringInput::from(&*fm),
+        None,
+    );
+    let mut parser = Parser::new_from(lexer);
+    let module = parser.parse_module().unwrap();
+
+    return (cm, module);
+}
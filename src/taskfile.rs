@@ -0,0 +1,571 @@
+use super::runner::{find_key_line, Runner, Task, TaskFlag};
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+use serde_yaml::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const TASKFILE_NAMES: [&str; 6] = [
+    "Taskfile.yml",
+    "taskfile.yml",
+    "Taskfile.yaml",
+    "taskfile.yaml",
+    "Taskfile.dist.yml",
+    "Taskfile.dist.yaml",
+];
+
+// Guard against include cycles
+const MAX_INCLUDE_DEPTH: usize = 10;
+
+#[derive(Debug, Deserialize)]
+struct TaskfileConfig {
+    #[serde(default)]
+    tasks: HashMap<String, Value>,
+    #[serde(default)]
+    includes: HashMap<String, TaskfileInclude>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TaskDefinition {
+    desc: Option<String>,
+    #[serde(default)]
+    internal: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum TaskfileInclude {
+    Path(String),
+    Definition(IncludeDefinition),
+}
+
+#[derive(Debug, Deserialize)]
+struct IncludeDefinition {
+    taskfile: String,
+    #[serde(default)]
+    optional: bool,
+    #[serde(default)]
+    internal: bool,
+    #[serde(default)]
+    flatten: bool,
+}
+
+fn parse_taskfile(content: &str) -> Result<TaskfileConfig> {
+    return serde_yaml::from_str(content).context("Failed to parse Taskfile");
+}
+
+/// Includes may point to a directory containing a Taskfile
+fn resolve_include(base_dir: &Path, path: &str) -> Option<PathBuf> {
+    let path = base_dir.join(path);
+
+    if path.is_dir() {
+        return TASKFILE_NAMES
+            .iter()
+            .map(|name| path.join(name))
+            .find(|candidate| candidate.exists());
+    }
+
+    if path.exists() {
+        return Some(path);
+    }
+
+    return None;
+}
+
+/// Command lines of a task from `cmds:`, `cmd:` or the short string and
+/// list forms
+fn task_command(value: &Value) -> Option<String> {
+    let cmds = match value {
+        Value::String(command) => return Some(command.clone()),
+        Value::Sequence(_) => value,
+        Value::Mapping(_) => {
+            if let Some(command) = value.get("cmd").and_then(|c| c.as_str()) {
+                return Some(command.to_string());
+            }
+            value.get("cmds")?
+        }
+        _ => return None,
+    };
+
+    let lines: Vec<String> = cmds
+        .as_sequence()?
+        .iter()
+        .filter_map(|cmd| {
+            if let Some(command) = cmd.as_str() {
+                return Some(command.to_string());
+            }
+            if let Some(command) = cmd.get("cmd").and_then(|c| c.as_str()) {
+                return Some(command.to_string());
+            }
+            let task = cmd.get("task").and_then(|t| t.as_str())?;
+            return Some(format!("task: {}", task));
+        })
+        .collect();
+
+    if lines.is_empty() {
+        return None;
+    }
+
+    return Some(lines.join("\n"));
+}
+
+/// Variables that can be passed as `NAME=value` from `requires.vars`, which
+/// may list allowed values with `enum`, and the task level `vars`
+fn task_vars(value: &Value) -> Vec<TaskFlag> {
+    let mut flags: Vec<TaskFlag> = Vec::new();
+
+    let required = value
+        .get("requires")
+        .and_then(|r| r.get("vars"))
+        .and_then(|v| v.as_sequence());
+
+    for var in required.into_iter().flatten() {
+        if let Some(name) = var.as_str() {
+            flags.push(TaskFlag::new(
+                format!("{}=", name),
+                Some("required variable"),
+            ));
+            continue;
+        }
+
+        let Some(name) = var.get("name").and_then(|n| n.as_str()) else {
+            continue;
+        };
+
+        let values: Vec<&str> = var
+            .get("enum")
+            .and_then(|e| e.as_sequence())
+            .map(|e| e.iter().filter_map(|v| v.as_str()).collect())
+            .unwrap_or_default();
+
+        if values.is_empty() {
+            flags.push(TaskFlag::new(
+                format!("{}=", name),
+                Some("required variable"),
+            ));
+        }
+
+        for allowed in values {
+            flags.push(TaskFlag::new(
+                format!("{}={}", name, allowed),
+                Some("required variable"),
+            ));
+        }
+    }
+
+    if let Some(vars) = value.get("vars").and_then(|v| v.as_mapping()) {
+        for name in vars.keys().filter_map(|k| k.as_str()) {
+            let flag = format!("{}=", name);
+            if !flags.iter().any(|f| f.flag.starts_with(&flag)) {
+                flags.push(TaskFlag::new(flag, Some("variable")));
+            }
+        }
+    }
+
+    return flags;
+}
+
+/// Variable assignments must come before `--` to not end up in CLI_ARGS
+fn is_var_assignment(arg: &str) -> bool {
+    let Some((name, _)) = arg.split_once('=') else {
+        return false;
+    };
+
+    return !name.is_empty()
+        && !name.starts_with(|c: char| c.is_ascii_digit())
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+}
+
+fn collect_tasks(
+    content: &str,
+    path: &Path,
+    prefix: &str,
+    internal: bool,
+    depth: usize,
+    out: &mut Vec<Task>,
+    files: &mut Vec<PathBuf>,
+) -> Result<()> {
+    let config = parse_taskfile(content)?;
+    let base_dir = path.parent().unwrap_or(Path::new(""));
+
+    let mut names: Vec<&String> = config.tasks.keys().collect();
+    names.sort();
+
+    for name in names {
+        // Tasks can also be a plain command string or a list of commands
+        let value = &config.tasks[name];
+        let definition: Option<TaskDefinition> = if value.is_mapping() {
+            serde_yaml::from_value(value.clone()).ok()
+        } else {
+            None
+        };
+
+        let (description, task_internal) = match definition {
+            Some(definition) => (definition.desc, definition.internal),
+            None => (None, false),
+        };
+
+        let mut task = Task::new(format!("{}{}", prefix, name));
+        task.description = description;
+        task.command = task_command(value);
+        task.source = Some(path.to_path_buf());
+        task.line = find_key_line(content, &["tasks", name]);
+        task.hidden = internal || task_internal;
+        task.flags = task_vars(value);
+        out.push(task);
+    }
+
+    if depth >= MAX_INCLUDE_DEPTH {
+        return Ok(());
+    }
+
+    let mut namespaces: Vec<&String> = config.includes.keys().collect();
+    namespaces.sort();
+
+    for namespace in namespaces {
+        let (path, optional, include_internal, flatten) = match &config.includes[namespace] {
+            TaskfileInclude::Path(path) => (path.as_str(), false, false, false),
+            TaskfileInclude::Definition(definition) => (
+                definition.taskfile.as_str(),
+                definition.optional,
+                definition.internal,
+                definition.flatten,
+            ),
+        };
+
+        // Templated paths such as "./{{OS}}.yml" cannot be resolved statically
+        if path.contains("{{") {
+            continue;
+        }
+
+        let Some(include_path) = resolve_include(base_dir, path) else {
+            if optional {
+                // Creating it changes the tasks
+                files.push(base_dir.join(path));
+                continue;
+            }
+            bail!("Included Taskfile '{}' not found", path);
+        };
+
+        let content = fs::read_to_string(&include_path)
+            .with_context(|| format!("Failed to read {}", include_path.display()))?;
+        files.push(include_path.clone());
+
+        let include_prefix = if flatten {
+            prefix.to_string()
+        } else {
+            format!("{}{}:", prefix, namespace)
+        };
+
+        collect_tasks(
+            &content,
+            &include_path,
+            &include_prefix,
+            internal || include_internal,
+            depth + 1,
+            out,
+            files,
+        )
+        .with_context(|| format!("Failed to parse {}", include_path.display()))?;
+    }
+
+    return Ok(());
+}
+
+pub struct TaskfileRunner {
+    tasks: Vec<Task>,
+    /// Included Taskfiles, also optional ones that do not exist
+    includes: Vec<String>,
+}
+
+impl TaskfileRunner {
+    pub fn new() -> Self {
+        return TaskfileRunner {
+            tasks: Vec::new(),
+            includes: Vec::new(),
+        };
+    }
+
+    /// The tasks and the included files
+    fn read_taskfile() -> Result<(Vec<Task>, Vec<String>)> {
+        let mut entries: Vec<Task> = Vec::new();
+        let mut files: Vec<PathBuf> = Vec::new();
+
+        let Some(path) = TASKFILE_NAMES.iter().find(|name| Path::new(name).exists()) else {
+            return Ok((entries, Vec::new()));
+        };
+
+        let content =
+            fs::read_to_string(path).with_context(|| format!("Failed to read {}", path))?;
+
+        collect_tasks(
+            &content,
+            Path::new(path),
+            "",
+            false,
+            0,
+            &mut entries,
+            &mut files,
+        )?;
+
+        let files = files
+            .iter()
+            .map(|file| file.to_string_lossy().to_string())
+            .collect();
+
+        return Ok((entries, files));
+    }
+}
+
+impl Runner for TaskfileRunner {
+    fn name(&self) -> &'static str {
+        return "Taskfile.yml";
+    }
+
+    fn marker_files(&self) -> Vec<&str> {
+        return TASKFILE_NAMES.to_vec();
+    }
+
+    fn cache_files(&self) -> Vec<&str> {
+        return self.includes.iter().map(String::as_str).collect();
+    }
+
+    fn tasks(&self) -> &Vec<Task> {
+        return &self.tasks;
+    }
+
+    fn tasks_mut(&mut self) -> &mut Vec<Task> {
+        return &mut self.tasks;
+    }
+
+    fn load(&mut self) -> Result<()> {
+        (self.tasks, self.includes) =
+            TaskfileRunner::read_taskfile().context("Failed to read Taskfile")?;
+        return Ok(());
+    }
+
+    fn command(&self, task: &str, args: &[String]) -> Result<Command> {
+        let mut go_task = Command::new("task");
+        let (vars, cli_args): (Vec<&String>, Vec<&String>) =
+            args.iter().partition(|arg| is_var_assignment(arg));
+
+        go_task.arg(task).args(vars).arg("--").args(cli_args);
+        return Ok(go_task);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_tasks(content: &str) -> Vec<Task> {
+        let mut entries = Vec::new();
+        collect_tasks(
+            content,
+            Path::new("Taskfile.yml"),
+            "",
+            false,
+            0,
+            &mut entries,
+            &mut Vec::new(),
+        )
+        .unwrap();
+        return entries;
+    }
+
+    #[test]
+    fn test_parse_tasks() {
+        let yaml = r#"
+version: '3'
+
+tasks:
+  build:
+    desc: Build the app
+    cmds:
+      - go build ./...
+      - task: lint
+
+  lint: golangci-lint run
+
+  test:
+    - go test ./...
+
+  setup:
+    internal: true
+    cmds:
+      - cmd: go mod download
+
+  empty:
+"#;
+        let entries = parse_tasks(yaml);
+        let summary: Vec<(&str, Option<&str>, bool)> = entries
+            .iter()
+            .map(|t| (t.name.as_str(), t.description.as_deref(), t.hidden))
+            .collect();
+
+        assert_eq!(
+            summary,
+            vec![
+                ("build", Some("Build the app"), false),
+                ("empty", None, false),
+                ("lint", None, false),
+                ("setup", None, true),
+                ("test", None, false),
+            ]
+        );
+
+        assert_eq!(
+            entries[0].command.as_deref(),
+            Some("go build ./...\ntask: lint")
+        );
+        assert_eq!(entries[0].line, Some(5));
+        assert_eq!(entries[1].command, None);
+        assert_eq!(entries[2].command.as_deref(), Some("golangci-lint run"));
+        assert_eq!(entries[3].command.as_deref(), Some("go mod download"));
+        assert_eq!(entries[4].command.as_deref(), Some("go test ./..."));
+    }
+
+    #[test]
+    fn test_task_vars() {
+        let yaml = r#"
+version: '3'
+tasks:
+  deploy:
+    requires:
+      vars:
+        - VERSION
+        - name: ENV
+          enum: [dev, prod]
+    vars:
+      VERSION: latest
+      REGION: eu
+    cmds:
+      - ./deploy.sh
+"#;
+        let entries = parse_tasks(yaml);
+        let flags: Vec<&str> = entries[0].flags.iter().map(|f| f.flag.as_str()).collect();
+
+        assert_eq!(flags, vec!["VERSION=", "ENV=dev", "ENV=prod", "REGION="]);
+        assert_eq!(entries[0].flags[3].description.as_deref(), Some("variable"));
+    }
+
+    #[test]
+    fn test_is_var_assignment() {
+        assert!(is_var_assignment("ENV=prod"));
+        assert!(is_var_assignment("my_var="));
+        assert!(!is_var_assignment("--env=prod"));
+        assert!(!is_var_assignment("=x"));
+        assert!(!is_var_assignment("1X=y"));
+        assert!(!is_var_assignment("file.txt"));
+    }
+
+    #[test]
+    fn test_parse_without_tasks() {
+        let yaml = r#"
+version: '3'
+vars:
+  GREETING: Hello
+"#;
+        assert!(parse_tasks(yaml).is_empty());
+    }
+
+    #[test]
+    fn test_parse_invalid_yaml() {
+        assert!(parse_taskfile("this is not: valid: yaml:::").is_err());
+    }
+
+    #[test]
+    fn test_includes() {
+        let dir = std::env::temp_dir().join(format!("rt-taskfile-test-{}", std::process::id()));
+        fs::create_dir_all(dir.join("docs")).unwrap();
+
+        fs::write(
+            dir.join("docs/Taskfile.yml"),
+            "version: '3'\ntasks:\n  serve:\n    desc: Serve docs\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.join("Shared.yml"),
+            "version: '3'\ntasks:\n  fmt: gofmt\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.join("Secret.yml"),
+            "version: '3'\ntasks:\n  key: echo\n",
+        )
+        .unwrap();
+
+        let yaml = r#"
+version: '3'
+includes:
+  docs: ./docs
+  shared:
+    taskfile: ./Shared.yml
+    flatten: true
+  secret:
+    taskfile: ./Secret.yml
+    internal: true
+  missing:
+    taskfile: ./Missing.yml
+    optional: true
+  os: ./Taskfile_{{OS}}.yml
+tasks:
+  build: go build
+"#;
+
+        let mut entries = Vec::new();
+        let mut files = Vec::new();
+        collect_tasks(
+            yaml,
+            &dir.join("Taskfile.yml"),
+            "",
+            false,
+            0,
+            &mut entries,
+            &mut files,
+        )
+        .unwrap();
+        fs::remove_dir_all(&dir).ok();
+
+        let names: Vec<(&str, bool)> = entries
+            .iter()
+            .map(|entry| (entry.name.as_str(), entry.hidden))
+            .collect();
+
+        assert_eq!(
+            names,
+            vec![
+                ("build", false),
+                ("docs:serve", false),
+                ("secret:key", true),
+                ("fmt", false),
+            ]
+        );
+        assert_eq!(entries[1].description.as_deref(), Some("Serve docs"));
+        assert_eq!(
+            entries[1].source,
+            Some(dir.join("docs").join("Taskfile.yml"))
+        );
+        assert_eq!(
+            files,
+            vec![
+                dir.join("docs").join("Taskfile.yml"),
+                dir.join("./Missing.yml"),
+                dir.join("./Secret.yml"),
+                dir.join("./Shared.yml"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_missing_required_include() {
+        let yaml = "version: '3'\nincludes:\n  nope: ./does-not-exist.yml\n";
+        let mut entries = Vec::new();
+        let mut files = Vec::new();
+        let path = Path::new("Taskfile.yml");
+
+        assert!(collect_tasks(yaml, path, "", false, 0, &mut entries, &mut files).is_err());
+    }
+}
@@ -0,0 +1,51 @@
+use crate::runner::{Runner, Task, TaskParam};
+use anyhow::Result;
+use std::process::Command;
+
+/// In-memory runner for completion tests
+pub struct TestRunner {
+    pub tasks: Vec<Task>,
+    name: String,
+}
+
+impl TestRunner {
+    pub fn new(name: String, tasks: Vec<String>) -> Self {
+        let tasks = tasks.into_iter().map(Task::new).collect();
+        return TestRunner { tasks, name };
+    }
+}
+
+/// `deploy` with an `env` argument defaulting to `prod`
+pub fn deploy_task() -> Task {
+    let mut task = Task::new("deploy");
+    task.params.push(TaskParam {
+        name: "env".to_string(),
+        default: Some("prod".to_string()),
+        variadic: false,
+    });
+    return task;
+}
+
+impl Runner for TestRunner {
+    fn name(&self) -> &str {
+        return &self.name;
+    }
+
+    fn tasks(&self) -> &Vec<Task> {
+        return &self.tasks;
+    }
+
+    fn tasks_mut(&mut self) -> &mut Vec<Task> {
+        return &mut self.tasks;
+    }
+
+    fn load(&mut self) -> Result<()> {
+        return Ok(());
+    }
+
+    fn command(&self, task: &str, args: &[String]) -> Result<Command> {
+        let mut echo = Command::new("echo");
+        echo.arg(task).args(args);
+        return Ok(echo);
+    }
+}
@@ -0,0 +1,365 @@
+use crate::parallel::exit_code;
+use crate::signals;
+use anyhow::{bail, Context, Result};
+use globset::{Glob, GlobBuilder, GlobSet, GlobSetBuilder};
+use notify::event::{AccessKind, AccessMode};
+use notify::{Event, EventKind, RecursiveMode, Watcher};
+use std::env;
+use std::os::unix::process::CommandExt;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Quiet period after the last change before the task is restarted
+const DEBOUNCE: Duration = Duration::from_millis(200);
+/// Time a stopped task has to exit before it is killed
+const STOP_TIMEOUT: Duration = Duration::from_secs(3);
+
+const GLOB_CHARS: [char; 4] = ['*', '?', '[', '{'];
+
+/// Directories never watched: version control data, dependencies and build
+/// output. Writes there would restart the task in a loop.
+const IGNORED_DIRS: [&str; 5] = [".git", ".hg", ".svn", "node_modules", "target"];
+
+/// Glob with `*` not crossing directories, like in shells
+fn glob(pattern: &str) -> Result<Glob> {
+    return GlobBuilder::new(pattern)
+        .literal_separator(true)
+        .build()
+        .with_context(|| format!("Invalid glob '{}'", pattern));
+}
+
+/// Patterns relative to the base directory unless absolute. Paths without
+/// globs also cover the directory contents.
+fn absolute_patterns(base: &Path, patterns: &[String]) -> Vec<String> {
+    let mut absolute: Vec<String> = Vec::new();
+
+    for pattern in patterns {
+        let pattern = pattern.strip_prefix("./").unwrap_or(pattern);
+        let path = base.join(pattern).to_string_lossy().to_string();
+        let path = path.trim_end_matches('/').to_string();
+
+        if !pattern.contains(GLOB_CHARS) {
+            absolute.push(format!("{}/**", path));
+        }
+        absolute.push(path);
+    }
+
+    return absolute;
+}
+
+fn glob_set(patterns: &[String]) -> Result<GlobSet> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        builder.add(glob(pattern)?);
+    }
+
+    return builder.build().context("Invalid globs");
+}
+
+/// Directory to watch for a pattern and whether its subdirectories are needed
+fn watch_root(pattern: &Path) -> (PathBuf, RecursiveMode) {
+    let mut root = PathBuf::new();
+    let mut components = pattern.components();
+
+    for component in components.by_ref() {
+        if component.as_os_str().to_string_lossy().contains(GLOB_CHARS) {
+            let rest: Vec<_> = components.collect();
+            let is_deep = !rest.is_empty() || component.as_os_str() == "**";
+
+            let mode = if is_deep {
+                RecursiveMode::Recursive
+            } else {
+                RecursiveMode::NonRecursive
+            };
+            return (root, mode);
+        }
+
+        root.push(component);
+    }
+
+    if root.is_dir() {
+        return (root, RecursiveMode::Recursive);
+    }
+
+    let parent = root.parent().map(|p| p.to_path_buf()).unwrap_or_default();
+    return (parent, RecursiveMode::NonRecursive);
+}
+
+struct Matcher {
+    patterns: Vec<String>,
+    matches: GlobSet,
+    ignored: GlobSet,
+}
+
+impl Matcher {
+    /// Patterns and ignored outputs are relative to the base directory
+    /// unless absolute
+    fn new(base: &Path, patterns: &[String], outputs: &[String]) -> Result<Self> {
+        let patterns = absolute_patterns(base, patterns);
+
+        let mut ignored = absolute_patterns(base, outputs);
+        for dir in IGNORED_DIRS {
+            ignored.push(format!("**/{}/**", dir));
+        }
+
+        return Ok(Matcher {
+            matches: glob_set(&patterns)?,
+            ignored: glob_set(&ignored)?,
+            patterns,
+        });
+    }
+
+    fn is_match(&self, path: &Path) -> bool {
+        return self.matches.is_match(path) && !self.ignored.is_match(path);
+    }
+
+    /// Directories to watch. Directories inside recursively watched ones are
+    /// skipped and missing ones are replaced by their closest existing parent.
+    fn roots(&self) -> Vec<(PathBuf, RecursiveMode)> {
+        let mut roots: Vec<(PathBuf, RecursiveMode)> = Vec::new();
+
+        for pattern in &self.patterns {
+            let (mut root, mut mode) = watch_root(Path::new(pattern));
+
+            while !root.is_dir() {
+                let Some(parent) = root.parent() else {
+                    break;
+                };
+                root = parent.to_path_buf();
+                mode = RecursiveMode::NonRecursive;
+            }
+
+            roots.push((root, mode));
+        }
+
+        roots.sort_by(|a, b| a.0.cmp(&b.0));
+        roots.dedup();
+
+        // Skip directories already covered by a recursive watch
+        let is_covered = |(root, mode): &(PathBuf, RecursiveMode)| {
+            return roots.iter().any(|(other, other_mode)| {
+                return *other_mode == RecursiveMode::Recursive
+                    && root.starts_with(other)
+                    && (other != root || *mode != RecursiveMode::Recursive);
+            });
+        };
+
+        return roots
+            .iter()
+            .filter(|root| !is_covered(root))
+            .cloned()
+            .collect();
+    }
+}
+
+/// Start the task. It gets the terminal when rt has it.
+fn spawn(command: &mut Command, terminal: bool) -> Result<Child> {
+    let child = command
+        .spawn()
+        .with_context(|| format!("Failed to spawn {:?}", command.get_program()))?;
+
+    if terminal {
+        signals::set_foreground(child.id() as libc::pid_t);
+    }
+
+    return Ok(child);
+}
+
+/// Stop the task and everything it started. rt takes the terminal back
+/// first so it is not left to a dead process group.
+fn stop(child: &mut Child, terminal: bool) {
+    if terminal {
+        signals::take_terminal();
+    }
+
+    let group = -(child.id() as libc::pid_t);
+
+    unsafe {
+        libc::kill(group, libc::SIGTERM);
+    }
+
+    let started = Instant::now();
+    while started.elapsed() < STOP_TIMEOUT {
+        if let Ok(Some(_)) = child.try_wait() {
+            return;
+        }
+        thread::sleep(Duration::from_millis(20));
+    }
+
+    unsafe {
+        libc::kill(group, libc::SIGKILL);
+    }
+    child.wait().ok();
+}
+
+fn is_change(event: &Event) -> bool {
+    return match event.kind {
+        EventKind::Access(AccessKind::Close(AccessMode::Write)) => true,
+        EventKind::Access(_) => false,
+        _ => true,
+    };
+}
+
+/// Run the command and restart it whenever files matching the patterns
+/// change, except for its outputs. Runs until interrupted.
+pub fn watch(patterns: &[String], outputs: &[String], command: &mut Command) -> Result<i32> {
+    let project_dir = env::current_dir().context("Failed to get current directory")?;
+    let matcher = Matcher::new(&project_dir, patterns, outputs)?;
+
+    let (sender, receiver) = mpsc::channel();
+    let mut watcher =
+        notify::recommended_watcher(sender).context("Failed to start file watcher")?;
+
+    for (root, mode) in matcher.roots() {
+        watcher
+            .watch(&root, mode)
+            .with_context(|| format!("Failed to watch {}", root.display()))?;
+    }
+
+    signals::listen();
+
+    // The task gets its own process group so it can be stopped with
+    // everything it started. The group gets the terminal so the task can
+    // read from it.
+    let terminal = signals::owns_terminal();
+    command.process_group(0);
+    if terminal {
+        signals::foreground_on_exec(command);
+    }
+
+    eprintln!("[rt] Watching {}", patterns.join(", "));
+    let mut child = Some(spawn(command, terminal)?);
+    let mut changed_at: Option<Instant> = None;
+
+    loop {
+        if let Some(signal) = signals::received() {
+            if let Some(child) = &mut child {
+                stop(child, terminal);
+            }
+            return Ok(128 + signal);
+        }
+
+        match receiver.recv_timeout(Duration::from_millis(50)) {
+            Ok(Ok(event)) => {
+                if is_change(&event) && event.paths.iter().any(|path| matcher.is_match(path)) {
+                    changed_at = Some(Instant::now());
+                }
+            }
+            Ok(Err(e)) => eprintln!("[rt] Watch error: {}", e),
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => bail!("File watcher stopped"),
+        }
+
+        if let Some(running) = &mut child {
+            if let Some(status) = running.try_wait()? {
+                if terminal {
+                    signals::take_terminal();
+                }
+
+                // Ctrl-C only reaches the task while it has the terminal
+                let code = exit_code(status);
+                if terminal && code == 128 + libc::SIGINT {
+                    return Ok(code);
+                }
+
+                eprintln!("[rt] Exited with code {}, waiting for changes", code);
+                child = None;
+            }
+        }
+
+        if changed_at.is_some_and(|time| time.elapsed() >= DEBOUNCE) {
+            changed_at = None;
+
+            if let Some(running) = &mut child {
+                stop(running, terminal);
+            }
+
+            eprintln!("[rt] Files changed, restarting");
+            child = Some(spawn(command, terminal)?);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn glob_match(pattern: &str, path: &str) -> bool {
+        return glob(pattern).unwrap().compile_matcher().is_match(path);
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("src/*.ts", "src/a.ts"));
+        assert!(!glob_match("src/*.ts", "src/lib/a.ts"));
+        assert!(glob_match("src/**/*.ts", "src/a.ts"));
+        assert!(glob_match("src/**/*.ts", "src/lib/deep/a.ts"));
+        assert!(!glob_match("src/**/*.ts", "test/a.ts"));
+        assert!(glob_match("src/**", "src/lib/a.rs"));
+        assert!(glob_match("**/*.md", "README.md"));
+        assert!(glob_match("file?.txt", "file1.txt"));
+        assert!(!glob_match("file?.txt", "file10.txt"));
+        assert!(glob_match("[a-c]x", "bx"));
+        assert!(!glob_match("[!a-c]x", "bx"));
+        assert!(glob_match("{src,test}/**/*.{ts,tsx}", "test/a/b.tsx"));
+        assert!(!glob_match("{src,test}/**/*.{ts,tsx}", "lib/a.ts"));
+    }
+
+    #[test]
+    fn test_matcher() {
+        let matcher = Matcher::new(
+            Path::new("/project"),
+            &[
+                "src/**/*.rs".to_string(),
+                "./Cargo.toml".to_string(),
+                "/shared/config".to_string(),
+            ],
+            &[],
+        )
+        .unwrap();
+
+        assert!(matcher.is_match(Path::new("/project/src/main.rs")));
+        assert!(matcher.is_match(Path::new("/project/Cargo.toml")));
+        assert!(matcher.is_match(Path::new("/shared/config/app.json")));
+        assert!(!matcher.is_match(Path::new("/project/README.md")));
+        assert!(Matcher::new(Path::new("/project"), &["src/[".to_string()], &[]).is_err());
+    }
+
+    #[test]
+    fn test_ignored_dirs_and_outputs() {
+        let matcher = Matcher::new(
+            Path::new("/project"),
+            &["**/*".to_string()],
+            &["build/out.js".to_string(), "gen".to_string()],
+        )
+        .unwrap();
+
+        assert!(matcher.is_match(Path::new("/project/src/index.js")));
+        assert!(!matcher.is_match(Path::new("/project/.git/index")));
+        assert!(!matcher.is_match(Path::new("/project/node_modules/x/index.js")));
+        assert!(!matcher.is_match(Path::new("/project/app/node_modules/x.js")));
+        assert!(!matcher.is_match(Path::new("/project/target/debug/rt")));
+        assert!(!matcher.is_match(Path::new("/project/build/out.js")));
+        assert!(!matcher.is_match(Path::new("/project/gen/types.ts")));
+        assert!(matcher.is_match(Path::new("/project/build/other.js")));
+    }
+
+    #[test]
+    fn test_watch_root() {
+        assert_eq!(
+            watch_root(Path::new("/p/src/**/*.ts")),
+            (PathBuf::from("/p/src"), RecursiveMode::Recursive)
+        );
+        assert_eq!(
+            watch_root(Path::new("/p/*.json")),
+            (PathBuf::from("/p"), RecursiveMode::NonRecursive)
+        );
+        assert_eq!(
+            watch_root(Path::new("/p/missing/file.txt")),
+            (PathBuf::from("/p/missing"), RecursiveMode::NonRecursive)
+        );
+    }
+}
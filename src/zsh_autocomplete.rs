@@ -1,10 +1,12 @@
-use crate::runner::Runner;
+use crate::runner::{Runner, Task};
+use crate::shell_words::{command_words, words_after_cursor};
 
-type TaskList<'a> = Vec<(&'a str, &'a str)>;
+type TaskList<'a> = Vec<(&'a str, &'a Task)>;
 
-pub enum CompletionItems<'a> {
+pub(crate) enum CompletionItems<'a> {
     Files,
     Tasks(TaskList<'a>),
+    Args(Vec<(String, String)>),
 }
 
 fn get_zsh_autocomplete_code(items: &CompletionItems) -> String {
@@ -12,6 +14,25 @@ fn get_zsh_autocomplete_code(items: &CompletionItems) -> String {
 
     let tasks = match items {
         CompletionItems::Tasks(tasks) => tasks,
+        CompletionItems::Args(args) => {
+            out.push_str(r#"local -a _rt_args"#);
+            out.push('\n');
+
+            out.push_str(r#"_rt_args=( "#);
+            for (value, description) in args {
+                out.push_str(&format!(
+                    "'{}:{}' ",
+                    &zsh_escape(value),
+                    quote_escape(description)
+                ));
+            }
+            out.push(')');
+            out.push('\n');
+
+            out.push_str(r#"_describe 'argument' _rt_args"#);
+            out.push('\n');
+            return out;
+        }
         CompletionItems::Files => {
             out.push_str("_files .");
             out.push('\n');
@@ -24,9 +45,13 @@ fn get_zsh_autocomplete_code(items: &CompletionItems) -> String {
 
     out.push_str(r#"_rt_tasks=( "#);
     for (name, task) in tasks {
-        out.push_str(&format!("'{}:from {}' ", &zsh_escape(task), name));
+        out.push_str(&format!(
+            "'{}:{}' ",
+            &zsh_escape(&task.name),
+            quote_escape(&task_summary(name, task))
+        ));
     }
-    out.push_str(")");
+    out.push(')');
     out.push('\n');
 
     out.push_str(r#"_describe 'task' _rt_tasks"#);
@@ -35,31 +60,134 @@ fn get_zsh_autocomplete_code(items: &CompletionItems) -> String {
     return out;
 }
 
-fn get_completion_items<'a>(
+/// One line summary of a task: its description, the first line of its
+/// command or the runner it comes from
+pub(crate) fn task_summary(runner_name: &str, task: &Task) -> String {
+    if let Some(description) = &task.description {
+        return description.clone();
+    }
+
+    if let Some(line) = task.command.as_deref().and_then(|c| c.lines().next()) {
+        return line.to_string();
+    }
+
+    return format!("from {}", runner_name);
+}
+
+/// The partial word under the cursor, empty after a space
+pub(crate) fn current_word(lbuffer: &str) -> String {
+    return command_words(lbuffer).pop().unwrap_or_default();
+}
+
+/// rt's own flags allowed before the task names
+const RT_FLAGS: [&str; 6] = [
+    "-p",
+    "--parallel",
+    "--dry-run",
+    "--which",
+    "--exec",
+    "--watch",
+];
+
+fn is_task_name(runners: &[Box<dyn Runner>], word: &str) -> bool {
+    return runners
+        .iter()
+        .any(|runner| runner.find_task(word).is_some());
+}
+
+/// Tasks of all runners except the hidden and already typed ones
+fn task_list<'a>(runners: &'a [Box<dyn Runner>], typed: &[String]) -> TaskList<'a> {
+    let mut tasks: TaskList<'a> = Vec::new();
+
+    for runner in runners {
+        for task in runner.tasks() {
+            if task.hidden || typed.contains(&task.name) {
+                continue;
+            }
+            tasks.push((runner.name(), task));
+        }
+    }
+
+    return tasks;
+}
+
+pub(crate) fn get_completion_items<'a>(
     runners: &'a Vec<Box<dyn Runner>>,
     lbuffer: &str,
+    rbuffer: &str,
 ) -> CompletionItems<'a> {
-    let lbuffer = lbuffer.split("&&").last().unwrap_or(lbuffer);
-    let lbuffer = lbuffer.split(";").last().unwrap_or(lbuffer);
+    let words = command_words(lbuffer);
+
+    // rt -p build te<tab>
+    let flag = words
+        .get(1)
+        .map(|word| word.as_str())
+        .filter(|word| words.len() > 2 && RT_FLAGS.contains(word));
+    let mut first = if flag.is_some() { 2 } else { 1 };
+
+    // rt --watch 'src/**' bu<tab>
+    if flag == Some("--watch") && words.len() > 3 && !is_task_name(runners, &words[2]) {
+        first = 3;
+    }
+
+    // rt bu<tab>
+    if words.len() <= first + 1 {
+        return CompletionItems::Tasks(task_list(runners, &[]));
+    }
 
-    let arg_count = lbuffer.split_whitespace().count();
+    // Words typed before the one being completed, starting with the task
+    let typed = &words[first..words.len() - 1];
+    let parallel = matches!(flag, Some("-p") | Some("--parallel"));
+    // --exec and --watch run a single task
+    let single = matches!(flag, Some("--exec") | Some("--watch"));
+
+    // rt -p build te<tab> or rt build test li<tab> only take more tasks
+    let only_tasks = !single && typed.iter().all(|word| is_task_name(runners, word));
+    if only_tasks && (parallel || typed.len() > 1) {
+        return CompletionItems::Tasks(task_list(runners, typed));
+    }
 
     // rt build<space><tab> aka 'rt build '
     // or
     // rt build tar<tab> aka 'rt build tar'
-    if (arg_count > 1 && lbuffer.ends_with(' ')) || arg_count > 2 {
-        return CompletionItems::Files;
-    }
+    let task = typed[0].as_str();
+    let typed_args: Vec<&str> = typed[1..].iter().map(|word| word.as_str()).collect();
+
+    let mut args = match runners
+        .iter()
+        .find(|runner| runner.find_task(task).is_some())
+    {
+        Some(runner) => runner.arg_completions(task, &typed_args),
+        None => Vec::new(),
+    };
 
-    let mut tasks: TaskList<'a> = Vec::new();
+    // Do not offer flags already given after the cursor
+    let after = words_after_cursor(rbuffer);
+    args.retain(|(value, _)| !value.starts_with('-') || !after.contains(value));
 
-    for runner in runners {
-        for task in runner.tasks() {
-            tasks.push((runner.name(), task));
+    // The next word can be another task too. Only offered when the word
+    // starts like a task so other arguments still complete files.
+    let word = words.last().map(|word| word.as_str()).unwrap_or_default();
+    let tasks = task_list(runners, typed);
+    let is_task_prefix =
+        !word.is_empty() && tasks.iter().any(|(_, task)| task.name.starts_with(word));
+
+    if !only_tasks || !is_task_prefix {
+        if args.is_empty() {
+            return CompletionItems::Files;
         }
+        return CompletionItems::Args(args);
+    }
+
+    if args.is_empty() {
+        return CompletionItems::Tasks(tasks);
     }
 
-    return CompletionItems::Tasks(tasks);
+    for (name, task) in tasks {
+        args.push((task.name.clone(), task_summary(name, task)));
+    }
+
+    return CompletionItems::Args(args);
 }
 
 fn zsh_escape(task: &str) -> String {
@@ -75,47 +203,25 @@ fn zsh_escape(task: &str) -> String {
     return escaped;
 }
 
+/// Escape text for use inside a single quoted zsh string
+fn quote_escape(text: &str) -> String {
+    return text.replace('\'', "'\\''");
+}
+
 pub fn get_zsh_autocompletion(
     runners: &Vec<Box<dyn Runner>>,
     lbuffer: &str,
-    _rbuffer: &str,
+    rbuffer: &str,
 ) -> String {
-    let items = get_completion_items(runners, lbuffer);
+    let items = get_completion_items(runners, lbuffer, rbuffer);
     return get_zsh_autocomplete_code(&items);
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use anyhow::Result;
-    pub struct TestRunner {
-        tasks: Vec<String>,
-        name: String,
-    }
-
-    impl TestRunner {
-        pub fn new(name: String, tasks: Vec<String>) -> Self {
-            return TestRunner { tasks, name };
-        }
-    }
-
-    impl Runner for TestRunner {
-        fn name(&self) -> &str {
-            return &self.name;
-        }
-
-        fn tasks(&self) -> &Vec<String> {
-            return &self.tasks;
-        }
-
-        fn load(&mut self) -> Result<()> {
-            return Ok(());
-        }
-
-        fn run(&self, _task: &str, _args: &[String]) -> Result<i32> {
-            return Ok(0);
-        }
-    }
+    use crate::runner::TaskFlag;
+    use crate::test_runner::{deploy_task, TestRunner};
 
     #[test]
     fn test_get_completion_tasks() {
@@ -130,30 +236,34 @@ mod tests {
         ));
         let runners: Vec<Box<dyn Runner>> = vec![runner1, runner2];
 
-        let result = get_completion_items(&runners, "rt ");
+        let result = get_completion_items(&runners, "rt ", "");
         let CompletionItems::Tasks(tasks) = result else {
             panic!("Expected CompletionItems::Tasks");
         };
         assert_eq!(tasks.len(), 3);
 
         // lbuffer with two words and space
-        let result = get_completion_items(&runners, "rt build ");
+        let result = get_completion_items(&runners, "rt build ", "");
         assert!(matches!(result, CompletionItems::Files));
 
         // lbuffer with three words
-        let result = get_completion_items(&runners, "rt build something");
+        let result = get_completion_items(&runners, "rt build something", "");
         assert!(matches!(result, CompletionItems::Files));
 
         // lbuffer with partial word
-        let result = get_completion_items(&runners, "rt fo");
+        let result = get_completion_items(&runners, "rt fo", "");
 
         let CompletionItems::Tasks(tasks) = result else {
             panic!("Expected CompletionItems::Tasks");
         };
 
         // Intentionally return bar to because zsh will do the final filtering
+        let names: Vec<(&str, &str)> = tasks
+            .iter()
+            .map(|(runner, task)| (*runner, task.name.as_str()))
+            .collect();
         assert_eq!(
-            tasks,
+            names,
             vec![
                 ("runner1", "foo"),
                 ("runner1", "bar"),
@@ -171,40 +281,230 @@ mod tests {
 
         let runners: Vec<Box<dyn Runner>> = vec![runner1];
 
-        let result = get_completion_items(&runners, "ls && rt fo");
+        let result = get_completion_items(&runners, "ls && rt fo", "");
         let CompletionItems::Tasks(tasks) = result else {
             panic!("Expected CompletionItems::Tasks");
         };
         assert_eq!(tasks.len(), 2);
 
-        let result = get_completion_items(&runners, "ls && ls && rt fo");
+        let result = get_completion_items(&runners, "ls && ls && rt fo", "");
         let CompletionItems::Tasks(tasks) = result else {
             panic!("Expected CompletionItems::Tasks");
         };
         assert_eq!(tasks.len(), 2);
 
-        let result = get_completion_items(&runners, "ls&&rt fo");
+        let result = get_completion_items(&runners, "ls&&rt fo", "");
         let CompletionItems::Tasks(tasks) = result else {
             panic!("Expected CompletionItems::Tasks");
         };
         assert_eq!(tasks.len(), 2);
 
-        let result = get_completion_items(&runners, "ls;rt fo");
+        let result = get_completion_items(&runners, "ls;rt fo", "");
         let CompletionItems::Tasks(tasks) = result else {
             panic!("Expected CompletionItems::Tasks");
         };
         assert_eq!(tasks.len(), 2);
 
-        let result = get_completion_items(&runners, "ls; rt fo");
+        let result = get_completion_items(&runners, "ls; rt fo", "");
         let CompletionItems::Tasks(tasks) = result else {
             panic!("Expected CompletionItems::Tasks");
         };
         assert_eq!(tasks.len(), 2);
 
-        let result = get_completion_items(&runners, "ls ; rt fo");
+        let result = get_completion_items(&runners, "ls ; rt fo", "");
         let CompletionItems::Tasks(tasks) = result else {
             panic!("Expected CompletionItems::Tasks");
         };
         assert_eq!(tasks.len(), 2);
     }
+
+    #[test]
+    fn test_arg_completions() {
+        let mut runner1 = TestRunner::new("runner1".to_string(), Vec::new());
+        runner1.tasks.push(deploy_task());
+
+        let runners: Vec<Box<dyn Runner>> = vec![Box::new(runner1)];
+
+        let result = get_completion_items(&runners, "rt deploy ", "");
+        let CompletionItems::Args(args) = result else {
+            panic!("Expected CompletionItems::Args");
+        };
+        assert_eq!(args, vec![("prod".to_string(), "env".to_string())]);
+
+        let result = get_completion_items(&runners, "rt deploy pr", "");
+        assert!(matches!(result, CompletionItems::Args(_)));
+
+        // No more arguments to offer
+        let result = get_completion_items(&runners, "rt deploy prod ", "");
+        assert!(matches!(result, CompletionItems::Files));
+
+        let code = get_zsh_autocomplete_code(&get_completion_items(&runners, "rt deploy ", ""));
+        assert!(code.contains("'prod:env'"));
+    }
+
+    #[test]
+    fn test_task_descriptions() {
+        let mut build = Task::new("build");
+        build.description = Some("Build it".to_string());
+        let namespaced = Task::new("ns:test");
+        let mut lint = Task::new("lint");
+        lint.command = Some("eslint 'src'\nprettier --check .".to_string());
+
+        let items = CompletionItems::Tasks(vec![
+            ("justfile", &build),
+            ("justfile", &namespaced),
+            ("justfile", &lint),
+        ]);
+        let code = get_zsh_autocomplete_code(&items);
+
+        assert!(code.contains("'build:Build it'"));
+        assert!(code.contains("'ns\\:test:from justfile'"));
+        assert!(code.contains(r#"'lint:eslint '\''src'\''' "#));
+    }
+
+    #[test]
+    fn test_tokenized_command_line() {
+        let runner1 = Box::new(TestRunner::new(
+            "runner1".to_string(),
+            vec!["deploy".to_string(), "foo".to_string()],
+        ));
+        let runners: Vec<Box<dyn Runner>> = vec![runner1];
+
+        for lbuffer in [
+            "ls || rt fo",
+            "cat x | rt fo",
+            "(cd app && rt fo",
+            "sudo -E rt fo",
+            "env X=1 rt fo",
+            "X=1 rtn fo",
+        ] {
+            let result = get_completion_items(&runners, lbuffer, "");
+            assert!(matches!(result, CompletionItems::Tasks(_)), "{}", lbuffer);
+        }
+
+        // The separator is quoted so this is still an argument of deploy
+        let result = get_completion_items(&runners, "rt deploy 'a && b' ", "");
+        assert!(matches!(result, CompletionItems::Files));
+
+        // Editing the task name in the middle of a line
+        let result = get_completion_items(&runners, "rt fo", "o --flag && ls");
+        assert!(matches!(result, CompletionItems::Tasks(_)));
+    }
+
+    #[test]
+    fn test_skips_flags_after_cursor() {
+        let mut runner = TestRunner::new("runner1".to_string(), vec!["test".to_string()]);
+        runner.tasks[0].flags = vec![
+            TaskFlag::new("--watch", None),
+            TaskFlag::new("--coverage", None),
+        ];
+        let runners: Vec<Box<dyn Runner>> = vec![Box::new(runner)];
+
+        let CompletionItems::Args(args) = get_completion_items(&runners, "rt test ", " --watch")
+        else {
+            panic!("Expected CompletionItems::Args");
+        };
+        assert_eq!(args, vec![("--coverage".to_string(), "option".to_string())]);
+    }
+
+    fn task_names(items: CompletionItems) -> Vec<String> {
+        let CompletionItems::Tasks(tasks) = items else {
+            panic!("Expected CompletionItems::Tasks");
+        };
+        return tasks.iter().map(|(_, task)| task.name.clone()).collect();
+    }
+
+    #[test]
+    fn test_multiple_tasks() {
+        let mut runner = TestRunner::new(
+            "runner1".to_string(),
+            vec!["build".to_string(), "test".to_string()],
+        );
+        runner.tasks.push(deploy_task());
+        let runners: Vec<Box<dyn Runner>> = vec![Box::new(runner)];
+
+        for lbuffer in [
+            "rt build te",
+            "rt build test ",
+            "rt -p build ",
+            "rt --parallel build te",
+        ] {
+            let names = task_names(get_completion_items(&runners, lbuffer, ""));
+            assert!(!names.contains(&"build".to_string()), "{}", lbuffer);
+            assert!(names.contains(&"deploy".to_string()), "{}", lbuffer);
+        }
+
+        assert_eq!(
+            task_names(get_completion_items(&runners, "rt -p ", "")).len(),
+            3
+        );
+
+        // Other arguments still complete files
+        let result = get_completion_items(&runners, "rt build src/", "");
+        assert!(matches!(result, CompletionItems::Files));
+        let result = get_completion_items(&runners, "rt build x te", "");
+        assert!(matches!(result, CompletionItems::Files));
+
+        // Both the arguments of the task and other tasks
+        let CompletionItems::Args(args) = get_completion_items(&runners, "rt deploy te", "") else {
+            panic!("Expected CompletionItems::Args");
+        };
+        let values: Vec<&str> = args.iter().map(|(value, _)| value.as_str()).collect();
+        assert_eq!(values, vec!["prod", "build", "test"]);
+    }
+
+    #[test]
+    fn test_skips_rt_flags() {
+        let mut runner = TestRunner::new("runner1".to_string(), vec!["build".to_string()]);
+        runner.tasks.push(deploy_task());
+        let runners: Vec<Box<dyn Runner>> = vec![Box::new(runner)];
+
+        for flag in ["--dry-run", "--which", "--exec", "--watch"] {
+            let lbuffer = format!("rt {} bu", flag);
+            let names = task_names(get_completion_items(&runners, &lbuffer, ""));
+            assert_eq!(names, vec!["build", "deploy"], "{}", flag);
+
+            let lbuffer = format!("rt {} deploy ", flag);
+            let CompletionItems::Args(args) = get_completion_items(&runners, &lbuffer, "") else {
+                panic!("Expected CompletionItems::Args for {}", flag);
+            };
+            assert_eq!(args, vec![("prod".to_string(), "env".to_string())]);
+        }
+
+        // Dry runs take multiple tasks, --exec and --watch only one
+        for (flag, tasks) in [("--dry-run", true), ("--which", true), ("--exec", false)] {
+            let lbuffer = format!("rt {} build de", flag);
+            let result = get_completion_items(&runners, &lbuffer, "");
+            assert_eq!(
+                matches!(result, CompletionItems::Tasks(_)),
+                tasks,
+                "{}",
+                flag
+            );
+        }
+
+        // The optional glob of --watch
+        let names = task_names(get_completion_items(&runners, "rt --watch 'src/**' bu", ""));
+        assert_eq!(names, vec!["build", "deploy"]);
+        let result = get_completion_items(&runners, "rt --watch 'src/**' deploy ", "");
+        assert!(matches!(result, CompletionItems::Args(_)));
+        let result = get_completion_items(&runners, "rt --watch build de", "");
+        assert!(matches!(result, CompletionItems::Files));
+    }
+
+    #[test]
+    fn test_hidden_tasks_are_not_completed() {
+        let mut runner = TestRunner::new("runner1".to_string(), vec!["foo".to_string()]);
+        let mut private = Task::new("_private");
+        private.hidden = true;
+        runner.tasks.push(private);
+
+        let runners: Vec<Box<dyn Runner>> = vec![Box::new(runner)];
+
+        let CompletionItems::Tasks(tasks) = get_completion_items(&runners, "rt ", "") else {
+            panic!("Expected CompletionItems::Tasks");
+        };
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].1.name, "foo");
+    }
 }